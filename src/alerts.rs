@@ -0,0 +1,630 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use daemon_controller::grpc::daemon::{DaemonState, MetricsResponse, StatusResponse};
+
+use crate::metrics::DerivedRates;
+
+/// Number of fired/resolved transitions kept for the Alerts panel
+const MAX_HISTORY: usize = 100;
+
+/// Rules used when none are configured
+pub const DEFAULT_RULES: [&str; 4] = [
+    "cpu_usage_percent > 90 for 30s",
+    "memory_ratio > 0.85",
+    "error_rate > 5",
+    "state == ERROR",
+];
+
+/// Quantity an alert rule is evaluated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    CpuUsagePercent,
+    MemoryBytes,
+    /// Memory usage as a fraction of the limit
+    MemoryRatio,
+    ConnectionsActive,
+    /// Requests per second
+    RequestRate,
+    /// Errors per second
+    ErrorRate,
+    /// Fraction of requests that failed
+    ErrorRatio,
+    State,
+}
+
+impl AlertMetric {
+    const ALL: [AlertMetric; 8] = [
+        Self::CpuUsagePercent,
+        Self::MemoryBytes,
+        Self::MemoryRatio,
+        Self::ConnectionsActive,
+        Self::RequestRate,
+        Self::ErrorRate,
+        Self::ErrorRatio,
+        Self::State,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CpuUsagePercent => "cpu_usage_percent",
+            Self::MemoryBytes => "memory_bytes",
+            Self::MemoryRatio => "memory_ratio",
+            Self::ConnectionsActive => "connections_active",
+            Self::RequestRate => "request_rate",
+            Self::ErrorRate => "error_rate",
+            Self::ErrorRatio => "error_ratio",
+            Self::State => "state",
+        }
+    }
+
+    /// Whether the rule is about daemon status rather than metrics
+    pub fn is_status(self) -> bool {
+        self == Self::State
+    }
+}
+
+/// Comparison operator in an alert rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    const ALL: [Comparison; 6] = [
+        Self::GreaterOrEqual,
+        Self::LessOrEqual,
+        Self::Equal,
+        Self::NotEqual,
+        Self::Greater,
+        Self::Less,
+    ];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+        }
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Greater => value > threshold,
+            Self::GreaterOrEqual => value >= threshold,
+            Self::Less => value < threshold,
+            Self::LessOrEqual => value <= threshold,
+            Self::Equal => value == threshold,
+            Self::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// A condition that fires an alert once it has held for a duration.
+///
+/// Written as `<metric> <op> <value> [for <duration>]`, e.g.
+/// `cpu_usage_percent > 90 for 30s` or `state == ERROR`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    /// Threshold, or the `DaemonState` value for state rules
+    pub threshold: f64,
+    pub for_duration: Duration,
+}
+
+impl AlertRule {
+    /// Current value of the rule's metric, if known
+    fn value(
+        &self,
+        status: Option<&StatusResponse>,
+        metrics: Option<&MetricsResponse>,
+        rates: Option<&DerivedRates>,
+    ) -> Option<f64> {
+        match self.metric {
+            AlertMetric::CpuUsagePercent => metrics.map(|m| m.cpu_usage_percent),
+            AlertMetric::MemoryBytes => metrics.map(|m| m.memory_bytes as f64),
+            AlertMetric::MemoryRatio => metrics
+                .filter(|m| m.memory_limit_bytes > 0)
+                .map(|m| m.memory_bytes as f64 / m.memory_limit_bytes as f64),
+            AlertMetric::ConnectionsActive => metrics.map(|m| m.connections_active as f64),
+            AlertMetric::RequestRate => rates.map(|r| r.current.requests_per_sec),
+            AlertMetric::ErrorRate => rates.map(|r| r.current.errors_per_sec),
+            AlertMetric::ErrorRatio => rates.and_then(|r| r.current.error_ratio),
+            AlertMetric::State => status.map(|s| s.state as f64),
+        }
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.metric.name(), self.comparison.symbol())?;
+        if self.metric == AlertMetric::State {
+            let state = DaemonState::try_from(self.threshold as i32).unwrap_or_default();
+            write!(f, "{}", state.label().to_uppercase())?;
+        } else {
+            write!(f, "{}", self.threshold)?;
+        }
+        if !self.for_duration.is_zero() {
+            write!(f, " for {}", format_duration(self.for_duration))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (comparison, position) = Comparison::ALL
+            .iter()
+            .filter_map(|c| s.find(c.symbol()).map(|pos| (*c, pos)))
+            .min_by_key(|(_, pos)| *pos)
+            .ok_or_else(|| format!("missing comparison operator in alert rule '{}'", s))?;
+
+        let metric_name = s[..position].trim();
+        let metric = AlertMetric::ALL
+            .into_iter()
+            .find(|m| m.name() == metric_name)
+            .ok_or_else(|| {
+                let known: Vec<&str> = AlertMetric::ALL.iter().map(|m| m.name()).collect();
+                format!(
+                    "unknown metric '{}' in alert rule (expected one of: {})",
+                    metric_name,
+                    known.join(", ")
+                )
+            })?;
+
+        let rest = s[position + comparison.symbol().len()..].trim();
+        let (value, for_duration) = match rest.split_once(" for ") {
+            Some((value, duration)) => (value.trim(), parse_duration(duration.trim())?),
+            None => (rest, Duration::ZERO),
+        };
+
+        let threshold = if metric == AlertMetric::State {
+            parse_state(value)? as i32 as f64
+        } else {
+            value
+                .parse()
+                .map_err(|_| format!("invalid threshold '{}' in alert rule", value))?
+        };
+
+        Ok(Self {
+            metric,
+            comparison,
+            threshold,
+            for_duration,
+        })
+    }
+}
+
+/// Parse a duration such as `500ms`, `30s`, `5m` or `1h`
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in duration '{}' (use ms, s, m or h)", s))?;
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", s))?;
+    let seconds = |per_unit: u64| {
+        amount
+            .checked_mul(per_unit)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration '{}' is too long", s))
+    };
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "s" => Ok(Duration::from_secs(amount)),
+        "m" => seconds(60),
+        "h" => seconds(60 * 60),
+        _ => Err(format!(
+            "unknown unit '{}' in duration '{}' (use ms, s, m or h)",
+            unit, s
+        )),
+    }
+}
+
+/// Format a duration in the largest unit accepted by [`parse_duration`] that
+/// holds it exactly
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if !millis.is_multiple_of(1000) {
+        return format!("{}ms", millis);
+    }
+    match millis / 1000 {
+        secs if secs.is_multiple_of(3600) => format!("{}h", secs / 3600),
+        secs if secs.is_multiple_of(60) => format!("{}m", secs / 60),
+        secs => format!("{}s", secs),
+    }
+}
+
+fn parse_state(s: &str) -> Result<DaemonState, String> {
+    let upper = s.to_uppercase();
+    let name = upper.strip_prefix("DAEMON_STATE_").unwrap_or(&upper);
+    DaemonState::from_str_name(&format!("DAEMON_STATE_{}", name))
+        .ok_or_else(|| format!("unknown daemon state '{}' in alert rule", s))
+}
+
+/// Whether an alert started or stopped firing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertTransition {
+    Fired,
+    Resolved,
+    /// Stopped firing because its metric is no longer known, e.g. after the
+    /// connection was lost
+    NoData,
+}
+
+/// A recorded change in an alert's state
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub timestamp: String,
+    pub rule: String,
+    pub transition: AlertTransition,
+    /// Metric value when the transition happened
+    pub value: Option<f64>,
+}
+
+/// Evaluation state of a single rule
+#[derive(Debug, Clone)]
+pub struct RuleState {
+    pub rule: AlertRule,
+    /// When the condition started holding
+    pending_since: Option<Instant>,
+    /// When the alert fired, while it is firing
+    pub firing_since: Option<Instant>,
+}
+
+/// Evaluates alert rules and records their firing/resolved history
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    pub rules: Vec<RuleState>,
+    pub history: VecDeque<AlertEvent>,
+    /// Newly fired alerts awaiting a terminal notification
+    notifications: Vec<String>,
+}
+
+impl AlertEngine {
+    /// Create an engine for the given rules
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    pending_since: None,
+                    firing_since: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Evaluate every rule against the latest daemon data.
+    ///
+    /// A firing rule whose metric is no longer known stops firing.
+    pub fn evaluate(
+        &mut self,
+        status: Option<&StatusResponse>,
+        metrics: Option<&MetricsResponse>,
+        rates: Option<&DerivedRates>,
+    ) {
+        let now = Instant::now();
+        let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+
+        for state in &mut self.rules {
+            let value = state.rule.value(status, metrics, rates);
+            let holds = value.map(|value| state.rule.comparison.holds(value, state.rule.threshold));
+
+            let transition = if holds == Some(true) {
+                let since = *state.pending_since.get_or_insert(now);
+                if state.firing_since.is_none()
+                    && now.duration_since(since) >= state.rule.for_duration
+                {
+                    state.firing_since = Some(now);
+                    Some(AlertTransition::Fired)
+                } else {
+                    None
+                }
+            } else {
+                state.pending_since = None;
+                state.firing_since.take().map(|_| match holds {
+                    Some(_) => AlertTransition::Resolved,
+                    None => AlertTransition::NoData,
+                })
+            };
+
+            if let Some(transition) = transition {
+                let rule = state.rule.to_string();
+                if transition == AlertTransition::Fired {
+                    self.notifications.push(format!("Alert: {}", rule));
+                }
+                if self.history.len() == MAX_HISTORY {
+                    self.history.pop_front();
+                }
+                self.history.push_back(AlertEvent {
+                    timestamp: timestamp.clone(),
+                    rule,
+                    transition,
+                    value: value.filter(|_| state.rule.metric != AlertMetric::State),
+                });
+            }
+        }
+    }
+
+    /// Whether any alert is currently firing
+    pub fn any_firing(&self) -> bool {
+        self.rules.iter().any(|r| r.firing_since.is_some())
+    }
+
+    /// Whether any alert on daemon status (or metrics) is firing
+    pub fn firing_for(&self, status: bool) -> bool {
+        self.rules
+            .iter()
+            .any(|r| r.firing_since.is_some() && r.rule.metric.is_status() == status)
+    }
+
+    /// Take the notifications raised since the last call
+    pub fn take_notifications(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notifications)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(s: &str) -> AlertRule {
+        s.parse().expect("valid rule")
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn bad_durations() {
+        assert_eq!(
+            parse_duration("30"),
+            Err("missing unit in duration '30' (use ms, s, m or h)".to_string())
+        );
+        assert_eq!(parse_duration("m"), Err("invalid duration 'm'".to_string()));
+        assert_eq!(
+            parse_duration("3d"),
+            Err("unknown unit 'd' in duration '3d' (use ms, s, m or h)".to_string())
+        );
+        assert_eq!(
+            parse_duration("99999999999999999999s"),
+            Err("invalid duration '99999999999999999999s'".to_string())
+        );
+    }
+
+    #[test]
+    fn overflowing_durations() {
+        let minutes = format!("{}m", u64::MAX / 60 + 1);
+        assert_eq!(
+            parse_duration(&minutes),
+            Err(format!("duration '{}' is too long", minutes))
+        );
+        let hours = format!("{}h", u64::MAX / 3600 + 1);
+        assert_eq!(
+            parse_duration(&hours),
+            Err(format!("duration '{}' is too long", hours))
+        );
+        assert!(parse_duration(&format!("{}h", u64::MAX / 3600)).is_ok());
+    }
+
+    #[test]
+    fn formatted_durations() {
+        for duration in ["500ms", "1500ms", "30s", "90s", "5m", "2h"] {
+            assert_eq!(format_duration(parse_duration(duration).unwrap()), duration);
+        }
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m");
+        assert_eq!(format_duration(Duration::from_millis(7_200_000)), "2h");
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(
+            rule("cpu_usage_percent > 90 for 30s"),
+            AlertRule {
+                metric: AlertMetric::CpuUsagePercent,
+                comparison: Comparison::Greater,
+                threshold: 90.0,
+                for_duration: Duration::from_secs(30),
+            }
+        );
+        assert_eq!(
+            rule("memory_ratio>=0.85").comparison,
+            Comparison::GreaterOrEqual
+        );
+        assert_eq!(rule("error_rate != 0").comparison, Comparison::NotEqual);
+        let state = rule("state == error");
+        assert_eq!(state.threshold, DaemonState::Error as i32 as f64);
+        assert_eq!(state.to_string(), "state == ERROR");
+        for default in DEFAULT_RULES {
+            assert_eq!(rule(default).to_string(), default);
+        }
+        assert_eq!(
+            rule("error_ratio > 0.5 for 500ms").to_string(),
+            "error_ratio > 0.5 for 500ms"
+        );
+    }
+
+    fn metrics(cpu_usage_percent: f64) -> MetricsResponse {
+        MetricsResponse {
+            cpu_usage_percent,
+            ..MetricsResponse::default()
+        }
+    }
+
+    /// Rule, transition and value of each recorded event
+    fn history(engine: &AlertEngine) -> Vec<(String, AlertTransition, Option<f64>)> {
+        engine
+            .history
+            .iter()
+            .map(|event| (event.rule.clone(), event.transition, event.value))
+            .collect()
+    }
+
+    #[test]
+    fn alerts_fire_and_resolve() {
+        let mut engine = AlertEngine::new(vec![rule("cpu_usage_percent > 90")]);
+        engine.evaluate(None, Some(&metrics(50.0)), None);
+        assert!(!engine.any_firing());
+        assert!(engine.history.is_empty());
+
+        engine.evaluate(None, Some(&metrics(95.0)), None);
+        assert!(engine.firing_for(false));
+        assert!(!engine.firing_for(true));
+        assert_eq!(
+            engine.take_notifications(),
+            ["Alert: cpu_usage_percent > 90"]
+        );
+
+        // Still firing: no new event or notification
+        engine.evaluate(None, Some(&metrics(99.0)), None);
+        assert_eq!(engine.history.len(), 1);
+        assert!(engine.take_notifications().is_empty());
+
+        engine.evaluate(None, Some(&metrics(90.0)), None);
+        assert!(!engine.any_firing());
+        assert!(engine.take_notifications().is_empty());
+        assert_eq!(
+            history(&engine),
+            [
+                (
+                    "cpu_usage_percent > 90".to_string(),
+                    AlertTransition::Fired,
+                    Some(95.0)
+                ),
+                (
+                    "cpu_usage_percent > 90".to_string(),
+                    AlertTransition::Resolved,
+                    Some(90.0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn alerts_wait_for_their_duration() {
+        let mut engine = AlertEngine::new(vec![rule("cpu_usage_percent > 90 for 30s")]);
+        engine.evaluate(None, Some(&metrics(95.0)), None);
+        assert!(!engine.any_firing());
+
+        // Dipping below the threshold restarts the wait
+        engine.rules[0].pending_since = Some(Instant::now() - Duration::from_secs(20));
+        engine.evaluate(None, Some(&metrics(50.0)), None);
+        engine.evaluate(None, Some(&metrics(95.0)), None);
+        assert!(!engine.any_firing());
+        let pending_since = engine.rules[0].pending_since.expect("pending");
+        assert!(pending_since.elapsed() < Duration::from_secs(20));
+
+        engine.rules[0].pending_since = Some(Instant::now() - Duration::from_secs(30));
+        engine.evaluate(None, Some(&metrics(95.0)), None);
+        assert!(engine.any_firing());
+        assert!(engine
+            .history
+            .iter()
+            .all(|e| e.transition == AlertTransition::Fired));
+        assert_eq!(engine.history.len(), 1);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut engine = AlertEngine::new(vec![rule("cpu_usage_percent > 90")]);
+        // Each evaluation fires or resolves the alert, with a value telling
+        // the events apart
+        for i in 0..MAX_HISTORY + 50 {
+            let cpu = if i % 2 == 0 {
+                100.0 + i as f64
+            } else {
+                i as f64 / 10.0
+            };
+            engine.evaluate(None, Some(&metrics(cpu)), None);
+        }
+        assert_eq!(engine.history.len(), MAX_HISTORY);
+        let values: Vec<Option<f64>> = engine.history.iter().map(|e| e.value).collect();
+        assert_eq!(values[0], Some(150.0));
+        assert_eq!(values[MAX_HISTORY - 1], Some(14.9));
+        assert_eq!(engine.take_notifications().len(), (MAX_HISTORY + 50) / 2);
+    }
+
+    #[test]
+    fn alerts_without_data_stop_firing() {
+        let mut engine =
+            AlertEngine::new(vec![rule("cpu_usage_percent > 90"), rule("state == ERROR")]);
+        let status = StatusResponse {
+            state: DaemonState::Error.into(),
+            ..StatusResponse::default()
+        };
+        engine.evaluate(Some(&status), Some(&metrics(95.0)), None);
+        assert!(engine.firing_for(true) && engine.firing_for(false));
+
+        engine.evaluate(None, None, None);
+        assert!(!engine.any_firing());
+        assert_eq!(
+            history(&engine),
+            [
+                (
+                    "cpu_usage_percent > 90".to_string(),
+                    AlertTransition::Fired,
+                    Some(95.0)
+                ),
+                ("state == ERROR".to_string(), AlertTransition::Fired, None),
+                (
+                    "cpu_usage_percent > 90".to_string(),
+                    AlertTransition::NoData,
+                    None
+                ),
+                ("state == ERROR".to_string(), AlertTransition::NoData, None),
+            ]
+        );
+
+        // Firing again once data returns
+        engine.evaluate(Some(&status), Some(&metrics(95.0)), None);
+        assert_eq!(engine.history.len(), 6);
+        assert_eq!(engine.take_notifications().len(), 4);
+    }
+
+    #[test]
+    fn bad_rules() {
+        let err = |s: &str| s.parse::<AlertRule>().expect_err("invalid rule");
+        assert_eq!(
+            err("cpu_usage_percent 90"),
+            "missing comparison operator in alert rule 'cpu_usage_percent 90'"
+        );
+        assert!(err("disk > 1").starts_with("unknown metric 'disk' in alert rule"));
+        assert_eq!(
+            err("error_rate > lots"),
+            "invalid threshold 'lots' in alert rule"
+        );
+        assert_eq!(
+            err("state == SLEEPING"),
+            "unknown daemon state 'SLEEPING' in alert rule"
+        );
+        assert_eq!(
+            err("error_rate > 5 for 1y"),
+            "unknown unit 'y' in duration '1y' (use ms, s, m or h)"
+        );
+    }
+}
@@ -1,6 +1,20 @@
-use std::time::Instant;
+use std::{cell::Cell, cmp::Reverse, path::PathBuf, time::Instant};
 
-use crate::grpc::daemon::{ControlCommand, DaemonState, MetricsResponse, StatusResponse};
+use crate::alerts::AlertEngine;
+use crate::audit::{self, AuditRecord};
+use crate::config::{Settings, DEFAULT_DAEMON_ADDRESS};
+use crate::events::CallError;
+use crate::fleet::Fleet;
+use crate::keymap::{Action, Context, Key, Keymap, Lookup};
+use crate::log_buffer::LogBuffer;
+use crate::log_export::{self, ExportOptions};
+use crate::log_fields;
+use crate::log_filter::{LogFilter, LogOrder};
+use crate::metrics::{HistoryWindow, MetricsHistory};
+use daemon_controller::grpc::{
+    daemon::{self, ControlCommand, DaemonState, LogLevel, MetricsResponse, StatusResponse},
+    socket_path,
+};
 
 /// Represents the connection status to the daemon
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -9,9 +23,41 @@ pub enum ConnectionStatus {
     Disconnected,
     Connecting,
     Connected,
+    /// Connection was lost and is being re-established
+    Reconnecting {
+        attempt: u32,
+        next_retry: Instant,
+    },
     Error(String),
 }
 
+/// Transport security of the daemon connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransportSecurity {
+    #[default]
+    Plaintext,
+    Tls,
+    /// TLS without verifying the server certificate
+    TlsUnverified,
+    /// Unix domain socket on this host
+    LocalSocket,
+}
+
+impl TransportSecurity {
+    /// Security of a connection made with the given settings
+    pub fn of(settings: &Settings) -> Self {
+        if socket_path(&settings.address).is_some() {
+            Self::LocalSocket
+        } else if !settings.address.starts_with("https://") {
+            Self::Plaintext
+        } else if settings.client.tls.insecure_skip_verify {
+            Self::TlsUnverified
+        } else {
+            Self::Tls
+        }
+    }
+}
+
 /// The currently focused panel in the UI
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FocusedPanel {
@@ -39,6 +85,20 @@ impl FocusedPanel {
     }
 }
 
+/// Top-level screen shown in the main content area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum View {
+    #[default]
+    Dashboard,
+    MetricsHistory,
+    /// Overview of every configured daemon
+    Fleet,
+    /// Control commands recorded in the audit file
+    Audit,
+    /// Read-only view of a saved log file
+    LogFile,
+}
+
 /// Available control actions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ControlAction {
@@ -65,6 +125,16 @@ impl ControlAction {
         }
     }
 
+    /// State the daemon should reach once the action completes
+    pub fn settled_state(self) -> DaemonState {
+        match self {
+            ControlAction::Stop => DaemonState::Stopped,
+            ControlAction::Start | ControlAction::Restart | ControlAction::Reload => {
+                DaemonState::Running
+            }
+        }
+    }
+
     pub fn to_command(self) -> ControlCommand {
         match self {
             ControlAction::Start => ControlCommand::Start,
@@ -75,12 +145,189 @@ impl ControlAction {
     }
 }
 
+/// How a control action is confirmed before it is sent, from the least
+/// to the most careful
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Confirmation {
+    /// Send immediately
+    #[default]
+    None,
+    /// Ask for a yes/no confirmation
+    Prompt,
+    /// Require typing the daemon name
+    TypeName,
+}
+
+/// Confirmation required by each control action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmPolicy {
+    pub start: Confirmation,
+    pub stop: Confirmation,
+    pub restart: Confirmation,
+    pub reload: Confirmation,
+}
+
+impl Default for ConfirmPolicy {
+    fn default() -> Self {
+        Self {
+            start: Confirmation::None,
+            stop: Confirmation::Prompt,
+            restart: Confirmation::Prompt,
+            reload: Confirmation::None,
+        }
+    }
+}
+
+impl ConfirmPolicy {
+    pub fn for_action(&self, action: ControlAction) -> Confirmation {
+        match action {
+            ControlAction::Start => self.start,
+            ControlAction::Stop => self.stop,
+            ControlAction::Restart => self.restart,
+            ControlAction::Reload => self.reload,
+        }
+    }
+}
+
+/// A control action waiting for the user to confirm it
+#[derive(Debug, Clone)]
+pub struct PendingConfirm {
+    pub action: ControlAction,
+    pub kind: Confirmation,
+    /// Names of the daemons the action is sent to; more than one for a bulk
+    /// action
+    pub targets: Vec<String>,
+    /// Text typed so far
+    pub input: String,
+}
+
+impl PendingConfirm {
+    /// Text to type when `kind` is [`Confirmation::TypeName`]: the daemon
+    /// name, or the number of daemons for a bulk action
+    pub fn expected_input(&self) -> String {
+        match self.targets.as_slice() {
+            [name] => name.clone(),
+            targets => targets.len().to_string(),
+        }
+    }
+
+    /// Whether Enter may send the action
+    pub fn is_satisfied(&self) -> bool {
+        self.kind != Confirmation::TypeName || self.input == self.expected_input()
+    }
+}
+
+/// Where a log entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogOrigin {
+    /// Logged by this application, e.g. about the connection
+    Controller,
+    /// Streamed from the daemon
+    #[default]
+    Daemon,
+}
+
+impl LogOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Controller => "controller",
+            Self::Daemon => "daemon",
+        }
+    }
+
+    /// Origin with the given label
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "controller" => Some(Self::Controller),
+            "daemon" => Some(Self::Daemon),
+            _ => None,
+        }
+    }
+}
+
 /// A log entry for display
 #[derive(Debug, Clone)]
 pub struct LogEntry {
-    pub timestamp: String,
-    pub level: String,
+    /// When the entry was logged, if the daemon sent a valid time
+    pub timestamp: Option<chrono::DateTime<chrono::Local>>,
+    pub level: LogLevel,
+    pub origin: LogOrigin,
     pub message: String,
+    /// Originating component within the daemon, empty for controller
+    /// messages
+    pub source: String,
+}
+
+/// Local time of a daemon log entry's `timestamp`, if it is valid.
+///
+/// The protocol leaves the unit unstated. It is assumed to be milliseconds
+/// since the Unix epoch, which is what the mock daemon sends.
+pub fn log_time(timestamp: u64) -> Option<chrono::DateTime<chrono::Local>> {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|t| t.with_timezone(&chrono::Local))
+}
+
+impl From<daemon::LogEntry> for LogEntry {
+    fn from(entry: daemon::LogEntry) -> Self {
+        Self {
+            timestamp: log_time(entry.timestamp),
+            // Levels added to the protocol after this build are shown as
+            // info rather than counted as errors
+            level: LogLevel::try_from(entry.level).unwrap_or(LogLevel::Info),
+            origin: LogOrigin::Daemon,
+            message: entry.message,
+            source: entry.source,
+        }
+    }
+}
+
+impl LogEntry {
+    /// Time of day as shown in the logs panel
+    pub fn clock(&self) -> String {
+        self.timestamp.map_or_else(
+            || "--:--:--".to_string(),
+            |t| t.format("%H:%M:%S").to_string(),
+        )
+    }
+
+    /// Structured fields of the message
+    pub fn fields(&self) -> Vec<(String, String)> {
+        log_fields::parse(&self.message)
+    }
+}
+
+/// Scroll position of a popup's text
+#[derive(Debug, Clone, Default)]
+pub struct TextScroll {
+    /// First row of the text shown
+    pub offset: usize,
+    /// Furthest the text can be scrolled, as of the last draw
+    pub max: Cell<usize>,
+}
+
+impl TextScroll {
+    /// Scroll the text by `delta` rows
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.offset = self.offset.saturating_add_signed(delta).min(self.max.get());
+    }
+}
+
+/// Log entry shown in the detail popup
+#[derive(Debug, Clone)]
+pub struct LogDetail {
+    pub entry: LogEntry,
+    pub scroll: TextScroll,
+}
+
+impl LogDetail {
+    pub fn new(entry: LogEntry) -> Self {
+        Self {
+            entry,
+            scroll: TextScroll::default(),
+        }
+    }
 }
 
 /// Main application state
@@ -104,21 +351,103 @@ pub struct App {
     /// Latest daemon metrics
     pub daemon_metrics: Option<MetricsResponse>,
 
+    /// Recent metrics samples for charts
+    pub metrics_history: MetricsHistory,
+
+    /// Window shown in the metrics history view
+    pub history_window: HistoryWindow,
+
+    /// Screen shown in the main content area
+    pub view: View,
+
+    /// Alert rules evaluated against each status and metrics update
+    pub alerts: AlertEngine,
+
     /// Log entries
-    pub logs: Vec<LogEntry>,
+    pub logs: LogBuffer,
+
+    /// Visible entry under the cursor in the logs panel, unless following
+    pub log_cursor: usize,
+
+    /// First visible entry drawn in the logs panel, moved by the renderer
+    /// only as far as needed to keep the cursor in view
+    pub log_top: Cell<usize>,
+
+    /// Whether the logs panel keeps the newest entry in view
+    pub log_follow: bool,
+
+    /// Visible entries added since the logs panel stopped following
+    pub log_unseen: usize,
+
+    /// Rows in the logs panel when it was last drawn, for paging
+    pub log_page: Cell<usize>,
+
+    /// Where the export keys write the logs
+    pub log_export: ExportOptions,
+
+    /// Saved log file shown by the read-only viewer, if any
+    pub viewed_log_file: Option<PathBuf>,
+
+    /// Search, filter and level settings of the logs panel
+    pub log_filter: LogFilter,
+
+    /// Log detail popup, if open
+    pub log_detail: Option<LogDetail>,
+
+    /// Key bindings, looked up by key handling and listed in the help
+    pub keymap: Keymap,
+
+    /// Keys pressed so far of an unfinished chord
+    pub pending_keys: Vec<Key>,
 
-    /// Log scroll offset
-    pub log_scroll: usize,
+    /// Help overlay, if open
+    pub help: Option<TextScroll>,
 
     /// gRPC endpoint address
     pub daemon_address: String,
 
+    /// Name of the configuration profile in use
+    pub profile: Option<String>,
+
+    /// Whether the connection is encrypted
+    pub security: TransportSecurity,
+
+    /// Authentication error returned by the latest refresh, if any
+    pub auth_failure: Option<CallError>,
+
+    /// Daemons shown in the fleet view, when started with `--fleet`
+    pub fleet: Option<Fleet>,
+
     /// When the app started (reserved for future uptime display)
     #[allow(dead_code)]
     pub start_time: Instant,
 
     /// Last status message
     pub status_message: Option<String>,
+
+    /// Whether a status/metrics refresh is in flight
+    pub refreshing: bool,
+
+    /// Control action awaiting a response from the daemon
+    pub pending_action: Option<ControlAction>,
+
+    /// Confirmation required before each control action is sent
+    pub confirm_policy: ConfirmPolicy,
+
+    /// Control action shown in the confirmation popup
+    pub confirm: Option<PendingConfirm>,
+
+    /// File control commands are recorded in, if a location is known
+    pub audit_file: Option<PathBuf>,
+
+    /// Records shown in the audit view, newest first
+    pub audit_records: Vec<AuditRecord>,
+
+    /// Selected row of the audit view
+    pub audit_selected: usize,
+
+    /// Number of ticks elapsed, drives spinner animation
+    pub tick_count: usize,
 }
 
 impl Default for App {
@@ -130,11 +459,38 @@ impl Default for App {
             selected_action: 0,
             daemon_status: None,
             daemon_metrics: None,
-            logs: Vec::new(),
-            log_scroll: 0,
-            daemon_address: "http://[::1]:50051".to_string(),
+            metrics_history: MetricsHistory::default(),
+            history_window: HistoryWindow::default(),
+            view: View::default(),
+            alerts: AlertEngine::default(),
+            logs: LogBuffer::default(),
+            log_cursor: 0,
+            log_top: Cell::new(0),
+            log_follow: true,
+            log_unseen: 0,
+            log_page: Cell::new(1),
+            log_export: ExportOptions::default(),
+            viewed_log_file: None,
+            log_filter: LogFilter::default(),
+            log_detail: None,
+            keymap: Keymap::default(),
+            pending_keys: Vec::new(),
+            help: None,
+            daemon_address: DEFAULT_DAEMON_ADDRESS.to_string(),
+            profile: None,
+            security: TransportSecurity::default(),
+            auth_failure: None,
+            fleet: None,
             start_time: Instant::now(),
             status_message: None,
+            refreshing: false,
+            pending_action: None,
+            confirm_policy: ConfirmPolicy::default(),
+            confirm: None,
+            audit_file: None,
+            audit_records: Vec::new(),
+            audit_selected: 0,
+            tick_count: 0,
         }
     }
 }
@@ -148,6 +504,31 @@ impl App {
         }
     }
 
+    /// Switch the dashboard to another daemon, resetting everything but the
+    /// fleet, the export options and the key bindings
+    pub fn switch_daemon(&mut self, daemon_address: String) {
+        *self = Self {
+            daemon_address,
+            fleet: self.fleet.take(),
+            log_export: std::mem::take(&mut self.log_export),
+            keymap: std::mem::take(&mut self.keymap),
+            start_time: self.start_time,
+            tick_count: self.tick_count,
+            ..Default::default()
+        };
+    }
+
+    /// Advance time-based UI state
+    pub fn tick(&mut self) {
+        self.tick_count = self.tick_count.wrapping_add(1);
+    }
+
+    /// Current frame of the in-flight spinner
+    pub fn spinner(&self) -> &'static str {
+        const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+        FRAMES[self.tick_count % FRAMES.len()]
+    }
+
     /// Request to quit the application
     pub fn quit(&mut self) {
         self.should_quit = true;
@@ -163,6 +544,111 @@ impl App {
         self.focused_panel = self.focused_panel.prev();
     }
 
+    /// Go back to the fleet view, if there is a fleet
+    pub fn show_fleet(&mut self) -> bool {
+        if self.fleet.is_none() {
+            return false;
+        }
+        self.view = View::Fleet;
+        true
+    }
+
+    /// Whether a popup or text input takes every key
+    pub fn captures_keys(&self) -> bool {
+        self.confirm.is_some()
+            || self.log_filter.input.is_some()
+            || self.log_detail.is_some()
+            || self.help.is_some()
+    }
+
+    /// Feed a key press to the keymap, returning the action it triggers in
+    /// the first of `contexts` binding it. Keys starting a chord are held
+    /// until the chord is finished or broken.
+    pub fn press_key(&mut self, contexts: &[Context], key: Key) -> Option<(Context, Action)> {
+        self.pending_keys.push(key);
+        match self.keymap.lookup(contexts, &self.pending_keys) {
+            Lookup::Action(context, action) => {
+                self.pending_keys.clear();
+                Some((context, action))
+            }
+            Lookup::Prefix => None,
+            Lookup::None => {
+                // A broken chord is dropped, but its last key may mean
+                // something on its own
+                let broken = self.pending_keys.len() > 1;
+                self.pending_keys.clear();
+                if broken {
+                    self.press_key(contexts, key)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Open the help overlay at the top
+    pub fn open_help(&mut self) {
+        self.help = Some(TextScroll::default());
+    }
+
+    /// Switch between the dashboard and the metrics history view
+    pub fn toggle_metrics_history(&mut self) {
+        self.view = match self.view {
+            View::MetricsHistory => View::Dashboard,
+            _ => View::MetricsHistory,
+        };
+    }
+
+    /// Switch between the dashboard and the audit view, re-reading the audit
+    /// file when opening it
+    pub fn toggle_audit(&mut self) {
+        if self.view == View::Audit {
+            self.view = View::Dashboard;
+            return;
+        }
+        let Some(path) = &self.audit_file else {
+            self.set_status_message("No audit file location known".to_string());
+            return;
+        };
+        match audit::read_records(path) {
+            Ok(mut records) => {
+                records.reverse();
+                self.audit_records = records;
+                self.audit_selected = 0;
+                self.view = View::Audit;
+            }
+            Err(e) => self.set_status_message(format!("Failed to read the audit file: {}", e)),
+        }
+    }
+
+    /// Show a saved log file in the read-only viewer, scrolled to its end
+    pub fn view_log_file(&mut self, path: PathBuf, entries: Vec<LogEntry>) {
+        self.logs = LogBuffer::new(entries.len());
+        for entry in entries {
+            self.logs.push(entry);
+        }
+        self.viewed_log_file = Some(path);
+        self.view = View::LogFile;
+        self.focused_panel = FocusedPanel::Logs;
+    }
+
+    /// Select the next (older) audit record
+    pub fn select_next_audit(&mut self) {
+        if self.audit_selected + 1 < self.audit_records.len() {
+            self.audit_selected += 1;
+        }
+    }
+
+    /// Select the previous (newer) audit record
+    pub fn select_prev_audit(&mut self) {
+        self.audit_selected = self.audit_selected.saturating_sub(1);
+    }
+
+    /// Cycle the metrics history window
+    pub fn next_history_window(&mut self) {
+        self.history_window = self.history_window.next();
+    }
+
     /// Select the next control action
     pub fn select_next_action(&mut self) {
         if self.selected_action < ControlAction::ALL.len() - 1 {
@@ -182,30 +668,209 @@ impl App {
         ControlAction::ALL[self.selected_action]
     }
 
-    /// Scroll logs up
+    /// Name identifying the daemon: its profile, or else its address
+    pub fn daemon_name(&self) -> &str {
+        self.profile.as_deref().unwrap_or(&self.daemon_address)
+    }
+
+    /// Move the log cursor up
     pub fn scroll_logs_up(&mut self) {
-        if self.log_scroll > 0 {
-            self.log_scroll -= 1;
-        }
+        self.scroll_logs(-1);
     }
 
-    /// Scroll logs down
+    /// Move the log cursor down
     pub fn scroll_logs_down(&mut self) {
-        if self.log_scroll < self.logs.len().saturating_sub(1) {
-            self.log_scroll += 1;
+        self.scroll_logs(1);
+    }
+
+    /// Move the log cursor up by a page
+    pub fn page_logs_up(&mut self) {
+        self.scroll_logs(-(self.log_page.get().max(1) as isize));
+    }
+
+    /// Move the log cursor down by a page
+    pub fn page_logs_down(&mut self) {
+        self.scroll_logs(self.log_page.get().max(1) as isize);
+    }
+
+    /// Move the log cursor to the oldest visible entry
+    pub fn scroll_logs_to_start(&mut self) {
+        self.scroll_logs(isize::MIN);
+    }
+
+    /// Move the log cursor to the newest visible entry and follow new ones
+    pub fn scroll_logs_to_end(&mut self) {
+        self.log_follow = true;
+        self.log_unseen = 0;
+    }
+
+    /// Move the log cursor by `delta` entries. Following stops when moving
+    /// away from the newest entry and resumes on reaching it.
+    fn scroll_logs(&mut self, delta: isize) {
+        let count = self.visible_log_count();
+        if count == 0 {
+            return;
+        }
+        let target = self
+            .log_selected(count)
+            .saturating_add_signed(delta)
+            .min(count - 1);
+        self.log_cursor = target;
+        if target == count - 1 {
+            self.scroll_logs_to_end();
+        } else {
+            self.log_follow = false;
+        }
+    }
+
+    /// Visible entry under the log cursor, given the number of visible
+    /// entries
+    pub fn log_selected(&self, count: usize) -> usize {
+        if self.log_follow {
+            count.saturating_sub(1)
+        } else {
+            self.log_cursor.min(count.saturating_sub(1))
+        }
+    }
+
+    /// Log entries passing the level, source and filter, in the chosen order
+    pub fn visible_logs(&self) -> Vec<&LogEntry> {
+        let mut visible: Vec<&LogEntry> = self
+            .logs
+            .iter()
+            .filter(|entry| self.log_filter.shows(entry))
+            .collect();
+        if self.log_filter.order == LogOrder::Level {
+            // Stable, so entries of a level stay oldest first
+            visible.sort_by_key(|entry| Reverse(entry.level));
+        }
+        visible
+    }
+
+    fn visible_log_count(&self) -> usize {
+        if self.log_filter.is_active() {
+            self.visible_logs().len()
+        } else {
+            self.logs.len()
+        }
+    }
+
+    /// Show only the next source seen in the logs, in alphabetical order,
+    /// wrapping around to every source
+    pub fn cycle_source_filter(&mut self) {
+        let mut sources: Vec<&str> = self
+            .logs
+            .iter()
+            .map(|entry| entry.source.as_str())
+            .filter(|source| !source.is_empty())
+            .collect();
+        sources.sort_unstable();
+        sources.dedup();
+        if sources.is_empty() {
+            self.set_status_message("No log sources seen yet".to_string());
+            return;
+        }
+        self.log_filter.cycle_source(&sources);
+        self.scroll_logs_to_end();
+    }
+
+    /// Open the detail popup for the entry under the log cursor
+    pub fn open_log_detail(&mut self) {
+        let visible = self.visible_logs();
+        let selected = visible.get(self.log_selected(visible.len())).copied();
+        self.log_detail = selected.cloned().map(LogDetail::new);
+    }
+
+    /// Scroll to the next visible entry matching the search, wrapping
+    /// around. The current entry is considered too when `inclusive` is set.
+    ///
+    /// Returns whether a match was found.
+    pub fn jump_to_match(&mut self, forward: bool, inclusive: bool) -> bool {
+        let Some(search) = &self.log_filter.search else {
+            return false;
+        };
+        let visible = self.visible_logs();
+        let len = visible.len();
+        if len == 0 {
+            return false;
+        }
+        let start = self.log_selected(len);
+        let offsets = if inclusive { 0..len } else { 1..len + 1 };
+        let found = offsets
+            .map(|offset| {
+                if forward {
+                    (start + offset) % len
+                } else {
+                    (start + len - offset % len) % len
+                }
+            })
+            .find(|&index| search.matches(visible[index]));
+        let Some(index) = found else {
+            return false;
+        };
+        if index == len - 1 {
+            self.scroll_logs_to_end();
+        } else {
+            self.log_cursor = index;
+            self.log_follow = false;
+        }
+        true
+    }
+
+    /// Write the logs to the export file: every buffered entry if `all` is
+    /// set, otherwise those passing the level and filter
+    pub fn export_logs(&mut self, all: bool) {
+        let (path, format) = self.log_export.target();
+        let result = if all {
+            log_export::export(self.logs.iter(), &path, format)
+        } else {
+            log_export::export(self.visible_logs(), &path, format)
+        };
+        match result {
+            Ok(count) => self.set_status_message(format!(
+                "Exported {} log lines to {}",
+                count,
+                path.display()
+            )),
+            Err(e) => self.set_status_message(format!(
+                "Failed to export logs to {}: {}",
+                path.display(),
+                e
+            )),
         }
     }
 
     /// Add a log entry
-    pub fn add_log(&mut self, level: &str, message: String) {
-        let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
-        self.logs.push(LogEntry {
-            timestamp,
-            level: level.to_string(),
+    pub fn add_log(&mut self, level: LogLevel, message: String) {
+        self.push_log(LogEntry {
+            timestamp: Some(chrono::Local::now()),
+            level,
+            origin: LogOrigin::Controller,
             message,
+            source: String::new(),
         });
-        // Auto-scroll to bottom
-        self.log_scroll = self.logs.len().saturating_sub(1);
+    }
+
+    /// Add a log entry streamed from the daemon
+    pub fn add_daemon_log(&mut self, entry: daemon::LogEntry) {
+        self.push_log(entry.into());
+    }
+
+    /// Append to the log buffer, keeping a paused logs panel on the same
+    /// entries
+    fn push_log(&mut self, entry: LogEntry) {
+        let shown = self.log_filter.shows(&entry);
+        let evicted = self.logs.push(entry);
+        if evicted.is_some_and(|e| self.log_filter.shows(&e)) {
+            self.log_top.set(self.log_top.get().saturating_sub(1));
+            self.log_cursor = self.log_cursor.saturating_sub(1);
+        }
+        if self.log_follow {
+            return;
+        }
+        if shown {
+            self.log_unseen += 1;
+        }
     }
 
     /// Update connection status
@@ -216,21 +881,43 @@ impl App {
     /// Update daemon status
     pub fn update_status(&mut self, status: StatusResponse) {
         self.daemon_status = Some(status);
+        self.evaluate_alerts();
     }
 
     /// Update daemon metrics
     pub fn update_metrics(&mut self, metrics: MetricsResponse) {
+        self.metrics_history.push(metrics);
         self.daemon_metrics = Some(metrics);
+        self.evaluate_alerts();
+    }
+
+    /// Forget the daemon's status and metrics once the connection is lost, so
+    /// alerts on them stop firing
+    pub fn clear_daemon_data(&mut self) {
+        self.daemon_status = None;
+        self.daemon_metrics = None;
+        self.evaluate_alerts();
+    }
+
+    /// Evaluate alert rules against the latest status and metrics
+    fn evaluate_alerts(&mut self) {
+        let rates = self
+            .daemon_metrics
+            .as_ref()
+            .and_then(|_| self.metrics_history.derived_rates());
+        self.alerts.evaluate(
+            self.daemon_status.as_ref(),
+            self.daemon_metrics.as_ref(),
+            rates.as_ref(),
+        );
     }
 
     /// Set a status message to display
-    #[allow(dead_code)]
     pub fn set_status_message(&mut self, message: String) {
         self.status_message = Some(message);
     }
 
     /// Clear the status message
-    #[allow(dead_code)]
     pub fn clear_status_message(&mut self) {
         self.status_message = None;
     }
@@ -238,16 +925,116 @@ impl App {
     /// Get the daemon state as a string
     pub fn daemon_state_string(&self) -> &str {
         match &self.daemon_status {
-            Some(status) => match DaemonState::try_from(status.state) {
-                Ok(DaemonState::Unknown) => "Unknown",
-                Ok(DaemonState::Starting) => "Starting",
-                Ok(DaemonState::Running) => "Running",
-                Ok(DaemonState::Stopping) => "Stopping",
-                Ok(DaemonState::Stopped) => "Stopped",
-                Ok(DaemonState::Error) => "Error",
-                Err(_) => "Invalid",
-            },
+            Some(status) => DaemonState::try_from(status.state)
+                .map(DaemonState::label)
+                .unwrap_or("Invalid"),
             None => "N/A",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// App keeping `capacity` log entries
+    fn app(capacity: usize) -> App {
+        App {
+            logs: LogBuffer::new(capacity),
+            ..App::default()
+        }
+    }
+
+    fn push(app: &mut App, level: LogLevel, message: &str) {
+        app.push_log(LogEntry {
+            timestamp: None,
+            level,
+            origin: LogOrigin::Daemon,
+            message: message.to_string(),
+            source: String::new(),
+        });
+    }
+
+    fn selected(app: &App) -> String {
+        let visible = app.visible_logs();
+        visible[app.log_selected(visible.len())].message.clone()
+    }
+
+    #[test]
+    fn daemon_entries_keep_their_level() {
+        let convert = |level: i32| {
+            LogEntry::from(daemon::LogEntry {
+                timestamp: 0,
+                level,
+                message: "hi".to_string(),
+                source: "http".to_string(),
+            })
+            .level
+        };
+        assert_eq!(convert(LogLevel::Debug as i32), LogLevel::Debug);
+        assert_eq!(convert(LogLevel::Warn as i32), LogLevel::Warn);
+        assert_eq!(convert(LogLevel::Error as i32), LogLevel::Error);
+        // Levels from a newer protocol
+        assert_eq!(convert(7), LogLevel::Info);
+        assert_eq!(convert(-1), LogLevel::Info);
+    }
+
+    #[test]
+    fn following_keeps_the_newest_entry_selected() {
+        let mut app = app(3);
+        for message in ["one", "two", "six", "ten"] {
+            push(&mut app, LogLevel::Info, message);
+        }
+        assert!(app.log_follow);
+        assert_eq!(app.log_unseen, 0);
+        assert_eq!(selected(&app), "ten");
+    }
+
+    #[test]
+    fn paused_panel_stays_on_its_entry() {
+        let mut app = app(4);
+        for message in ["one", "two", "six", "ten"] {
+            push(&mut app, LogLevel::Info, message);
+        }
+        app.scroll_logs_up();
+        assert!(!app.log_follow);
+        assert_eq!(selected(&app), "six");
+
+        // Each eviction shifts the cursor back with its entry
+        push(&mut app, LogLevel::Info, "new");
+        push(&mut app, LogLevel::Info, "old");
+        assert_eq!(app.log_cursor, 0);
+        assert_eq!(selected(&app), "six");
+        assert_eq!(app.log_unseen, 2);
+
+        // Once its entry is evicted the cursor stays on the oldest
+        push(&mut app, LogLevel::Info, "end");
+        assert_eq!(selected(&app), "ten");
+
+        app.scroll_logs_to_end();
+        assert!(app.log_follow);
+        assert_eq!(app.log_unseen, 0);
+        assert_eq!(selected(&app), "end");
+    }
+
+    #[test]
+    fn hidden_entries_are_not_counted() {
+        let mut app = app(3);
+        app.log_filter.min_level = Some(LogLevel::Warn);
+        push(&mut app, LogLevel::Info, "one");
+        push(&mut app, LogLevel::Warn, "two");
+        push(&mut app, LogLevel::Warn, "six");
+        app.scroll_logs_up();
+        assert_eq!(selected(&app), "two");
+
+        // Evicting a hidden entry leaves the visible ones where they were
+        push(&mut app, LogLevel::Debug, "ten");
+        assert_eq!(app.log_cursor, 0);
+        assert_eq!(selected(&app), "two");
+        assert_eq!(app.log_unseen, 0);
+
+        push(&mut app, LogLevel::Error, "new");
+        assert_eq!(app.log_unseen, 1);
+        assert_eq!(selected(&app), "six");
+    }
+}
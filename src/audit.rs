@@ -0,0 +1,410 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::ControlAction;
+use crate::config::Settings;
+use daemon_controller::grpc::daemon::ControlResponse;
+
+/// Where a control command was sent from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    Dashboard,
+    Cli,
+    /// Bulk action in the fleet view
+    Bulk,
+}
+
+impl Origin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dashboard => "dashboard",
+            Self::Cli => "cli",
+            Self::Bulk => "bulk",
+        }
+    }
+}
+
+/// One control command, as stored in the audit file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// RFC 3339 time the command was sent
+    pub timestamp: String,
+    pub user: String,
+    pub host: String,
+    pub origin: Origin,
+    /// Profile of the target daemon, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Address of the target daemon
+    pub target: String,
+    pub command: String,
+    pub success: bool,
+    /// Response message, or the error if the call failed
+    pub message: String,
+    pub latency_ms: u64,
+}
+
+impl AuditRecord {
+    /// Name identifying the target daemon: its profile, or else its address
+    pub fn daemon(&self) -> &str {
+        self.profile.as_deref().unwrap_or(&self.target)
+    }
+}
+
+/// Filters of the `audit` subcommand
+#[derive(Debug, Clone)]
+pub struct AuditQuery {
+    /// Text the daemon's profile or address contains
+    pub daemon: Option<String>,
+    pub command: Option<ControlAction>,
+    /// How far back records are shown
+    pub since: Option<Duration>,
+    /// Only show failed commands
+    pub failed: bool,
+    /// Number of most recent matching records shown
+    pub limit: usize,
+}
+
+impl AuditQuery {
+    /// The most recent records matching every filter, oldest first
+    pub fn select<'a>(&self, records: &'a [AuditRecord]) -> Vec<&'a AuditRecord> {
+        let cutoff = self
+            .since
+            .and_then(|since| chrono::Duration::from_std(since).ok())
+            .map(|since| chrono::Local::now() - since);
+        let mut matching: Vec<&AuditRecord> = records
+            .iter()
+            .filter(|record| {
+                self.daemon.as_ref().is_none_or(|text| {
+                    record.target.contains(text.as_str())
+                        || record
+                            .profile
+                            .as_ref()
+                            .is_some_and(|p| p.contains(text.as_str()))
+                })
+            })
+            .filter(|record| {
+                self.command
+                    .is_none_or(|action| record.command.eq_ignore_ascii_case(action.label()))
+            })
+            .filter(|record| !self.failed || !record.success)
+            .filter(|record| {
+                cutoff.is_none_or(|cutoff| {
+                    chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+                        .is_ok_and(|at| at >= cutoff)
+                })
+            })
+            .collect();
+        let skip = matching.len().saturating_sub(self.limit);
+        matching.split_off(skip)
+    }
+}
+
+/// Appends audit records for control commands sent to one daemon.
+///
+/// Records are written as JSON lines so concurrent writers only ever append
+/// whole lines.
+#[derive(Debug, Clone)]
+pub struct Auditor {
+    /// Audit file, or `None` if no location is known
+    file: Option<PathBuf>,
+    origin: Origin,
+    profile: Option<String>,
+    target: String,
+}
+
+impl Auditor {
+    pub fn new(settings: &Settings, origin: Origin) -> Self {
+        Self {
+            file: settings.audit_file.clone(),
+            origin,
+            profile: settings.profile.clone(),
+            target: settings.address.clone(),
+        }
+    }
+
+    /// Record a control command and its outcome
+    pub fn record(
+        &self,
+        action: ControlAction,
+        outcome: Result<&ControlResponse, String>,
+        latency: Duration,
+    ) -> Result<(), String> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let (success, message) = match outcome {
+            Ok(response) => (response.success, response.message.clone()),
+            Err(error) => (false, error),
+        };
+        let record = AuditRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            user: current_user(),
+            host: host_name(),
+            origin: self.origin,
+            profile: self.profile.clone(),
+            target: self.target.clone(),
+            command: action.label().to_string(),
+            success,
+            message,
+            latency_ms: latency.as_millis() as u64,
+        };
+        append(file, &record).map_err(|e| format!("{}: {}", file.display(), e))
+    }
+}
+
+fn append(path: &Path, record: &AuditRecord) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    // A single write keeps lines from interleaving with other processes
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Read every record in the audit file, oldest first.
+///
+/// A missing file has no records. Lines that do not parse, such as one cut
+/// short by a crash, are skipped.
+pub fn read_records(path: &Path) -> Result<Vec<AuditRecord>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn current_user() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn host_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auditor(file: PathBuf, profile: Option<&str>) -> Auditor {
+        Auditor {
+            file: Some(file),
+            origin: Origin::Cli,
+            profile: profile.map(str::to_string),
+            target: "http://127.0.0.1:50051".to_string(),
+        }
+    }
+
+    fn response(success: bool, message: &str) -> ControlResponse {
+        ControlResponse {
+            success,
+            message: message.to_string(),
+        }
+    }
+
+    fn record(
+        age: chrono::Duration,
+        profile: Option<&str>,
+        command: ControlAction,
+        success: bool,
+    ) -> AuditRecord {
+        AuditRecord {
+            timestamp: (chrono::Local::now() - age).to_rfc3339(),
+            user: "alice".to_string(),
+            host: "ops-1".to_string(),
+            origin: Origin::Dashboard,
+            profile: profile.map(str::to_string),
+            target: "http://10.0.0.1:50051".to_string(),
+            command: command.label().to_string(),
+            success,
+            message: String::new(),
+            latency_ms: 0,
+        }
+    }
+
+    fn query() -> AuditQuery {
+        AuditQuery {
+            daemon: None,
+            command: None,
+            since: None,
+            failed: false,
+            limit: 20,
+        }
+    }
+
+    /// Indexes into `records` of the selected records
+    fn selected(query: &AuditQuery, records: &[AuditRecord]) -> Vec<usize> {
+        query
+            .select(records)
+            .into_iter()
+            .map(|selected| {
+                records
+                    .iter()
+                    .position(|record| std::ptr::eq(record, selected))
+                    .expect("selected from records")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn record_format() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("audit").join("audit.jsonl");
+        auditor(path.clone(), Some("prod"))
+            .record(
+                ControlAction::Restart,
+                Ok(&response(true, "RESTART initiated")),
+                Duration::from_millis(42),
+            )
+            .expect("record");
+        auditor(path.clone(), None)
+            .record(
+                ControlAction::Stop,
+                Err("permission denied: Token is read-only".to_string()),
+                Duration::from_millis(7),
+            )
+            .expect("record");
+
+        let contents = fs::read_to_string(&path).expect("read audit file");
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("JSON line"))
+            .collect();
+        let keys = |line: &serde_json::Value| -> Vec<String> {
+            line.as_object().expect("object").keys().cloned().collect()
+        };
+        assert_eq!(
+            keys(&lines[0]),
+            [
+                "timestamp",
+                "user",
+                "host",
+                "origin",
+                "profile",
+                "target",
+                "command",
+                "success",
+                "message",
+                "latency_ms"
+            ]
+        );
+        assert_eq!(lines[0]["origin"], "cli");
+        assert_eq!(lines[0]["profile"], "prod");
+        assert_eq!(lines[0]["target"], "http://127.0.0.1:50051");
+        assert_eq!(lines[0]["command"], "Restart");
+        assert_eq!(lines[0]["success"], true);
+        assert_eq!(lines[0]["message"], "RESTART initiated");
+        assert_eq!(lines[0]["latency_ms"], 42);
+        let timestamp = lines[0]["timestamp"].as_str().expect("timestamp");
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+
+        // No profile key without a profile
+        assert!(!keys(&lines[1]).contains(&"profile".to_string()));
+        assert_eq!(lines[1]["success"], false);
+        assert_eq!(lines[1]["message"], "permission denied: Token is read-only");
+    }
+
+    #[test]
+    fn records_read_back() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("audit.jsonl");
+        assert!(read_records(&path).expect("missing file").is_empty());
+
+        let auditor = auditor(path.clone(), Some("prod"));
+        auditor
+            .record(
+                ControlAction::Reload,
+                Ok(&response(true, "Configuration reloaded")),
+                Duration::ZERO,
+            )
+            .expect("record");
+        // A line cut short by a crash is skipped
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(b"{\"timestamp\":\"2026-\n"))
+            .expect("append");
+        auditor
+            .record(
+                ControlAction::Start,
+                Err("timed out".to_string()),
+                Duration::ZERO,
+            )
+            .expect("record");
+
+        let records = read_records(&path).expect("read records");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].command, "Reload");
+        assert_eq!(records[0].daemon(), "prod");
+        assert!(records[0].success);
+        assert_eq!(records[1].command, "Start");
+        assert_eq!(records[1].message, "timed out");
+    }
+
+    #[test]
+    fn query_filters() {
+        let hours = chrono::Duration::hours;
+        let records = [
+            record(hours(48), Some("prod"), ControlAction::Restart, true),
+            record(hours(3), Some("staging"), ControlAction::Stop, false),
+            record(hours(2), None, ControlAction::Restart, false),
+            record(hours(1), Some("prod-eu"), ControlAction::Reload, true),
+        ];
+        assert_eq!(selected(&query(), &records), [0, 1, 2, 3]);
+
+        // The profile or, failing that, the address
+        let daemon = |text: &str| AuditQuery {
+            daemon: Some(text.to_string()),
+            ..query()
+        };
+        assert_eq!(selected(&daemon("prod"), &records), [0, 3]);
+        assert_eq!(selected(&daemon("10.0.0.1"), &records), [0, 1, 2, 3]);
+
+        let restarts = AuditQuery {
+            command: Some(ControlAction::Restart),
+            ..query()
+        };
+        assert_eq!(selected(&restarts, &records), [0, 2]);
+
+        let failed = AuditQuery {
+            failed: true,
+            ..query()
+        };
+        assert_eq!(selected(&failed, &records), [1, 2]);
+
+        let recent = AuditQuery {
+            since: Some(Duration::from_secs(24 * 60 * 60)),
+            ..query()
+        };
+        assert_eq!(selected(&recent, &records), [1, 2, 3]);
+
+        // The limit keeps the most recent matches
+        let last = AuditQuery { limit: 2, ..recent };
+        assert_eq!(selected(&last, &records), [2, 3]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{fs, net::SocketAddr, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use daemon_controller::mock::{self, ListenAddr, MockConfig, MockDaemon};
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// Run a simulated daemon implementing the DaemonService gRPC API
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:50051")]
+    listen: SocketAddr,
+
+    /// Listen on a Unix domain socket at this path instead of TCP
+    #[arg(long, value_name = "PATH", conflicts_with = "listen")]
+    socket: Option<PathBuf>,
+
+    /// Delay added before every unary response, in milliseconds
+    #[arg(long, default_value_t = 0)]
+    latency_ms: u64,
+
+    /// Probability in [0, 1] that a unary call fails
+    #[arg(long, default_value_t = 0.0)]
+    error_rate: f64,
+
+    /// Interval between synthetic log lines, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    log_interval_ms: u64,
+
+    /// Delay before an accepted control command starts changing state, in
+    /// milliseconds
+    #[arg(long, default_value_t = 0)]
+    control_delay_ms: u64,
+
+    /// Drop all connections every N seconds
+    #[arg(long)]
+    disconnect_every: Option<u64>,
+
+    /// Seconds to stay offline after a disconnect
+    #[arg(long, default_value_t = 5)]
+    downtime: u64,
+
+    /// PEM server certificate; enables TLS
+    #[arg(long, value_name = "PATH", requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM CA bundle; clients must present a certificate signed by it
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    client_ca: Option<PathBuf>,
+
+    /// Require this bearer token on every call
+    #[arg(long, value_name = "TOKEN")]
+    auth_token: Option<String>,
+
+    /// Also accept this bearer token, but deny control commands with it
+    #[arg(long, value_name = "TOKEN")]
+    read_only_token: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = Args::parse();
+
+    let daemon = MockDaemon::new(MockConfig {
+        latency: Duration::from_millis(args.latency_ms),
+        error_rate: args.error_rate.clamp(0.0, 1.0),
+        log_interval: Duration::from_millis(args.log_interval_ms),
+        control_delay: Duration::from_millis(args.control_delay_ms),
+        auth_token: args.auth_token,
+        read_only_token: args.read_only_token,
+        ..MockConfig::default()
+    });
+
+    if let Some(every) = args.disconnect_every {
+        let handle = daemon.handle();
+        let every = Duration::from_secs(every);
+        let downtime = Duration::from_secs(args.downtime);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                eprintln!("Injecting disconnect for {}s", downtime.as_secs());
+                handle.disconnect();
+                tokio::time::sleep(downtime).await;
+            }
+        });
+    }
+
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => {
+            let mut tls = ServerTlsConfig::new()
+                .identity(Identity::from_pem(fs::read(cert)?, fs::read(key)?));
+            if let Some(ca) = &args.client_ca {
+                tls = tls.client_ca_root(Certificate::from_pem(fs::read(ca)?));
+            }
+            Some(tls)
+        }
+        _ => None,
+    };
+
+    let listen = match args.socket {
+        #[cfg(unix)]
+        Some(path) => ListenAddr::Unix(path),
+        #[cfg(not(unix))]
+        Some(_) => return Err(eyre!("--socket is only supported on Unix")),
+        None => ListenAddr::Tcp(args.listen),
+    };
+    eprintln!(
+        "Mock daemon listening on {}{}",
+        listen,
+        if tls.is_some() { " (TLS)" } else { "" }
+    );
+    mock::serve(listen, daemon, Duration::from_secs(args.downtime), tls)
+        .await
+        .map_err(|e| eyre!(e))
+}
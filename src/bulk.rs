@@ -0,0 +1,438 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::app::ControlAction;
+use crate::audit::{Auditor, Origin};
+use crate::config::Settings;
+use crate::events::{CallError, Event};
+use daemon_controller::grpc::{
+    daemon::{DaemonState, StatusResponse},
+    DaemonClient,
+};
+
+/// Interval between status checks while waiting for a daemon to settle
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How a bulk action is spread over its targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkMode {
+    /// Send to every target at once
+    Parallel,
+    /// Work through the targets in batches, stopping at the first failure
+    Rolling,
+}
+
+impl BulkMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Parallel => "Parallel",
+            Self::Rolling => "Rolling",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Parallel => Self::Rolling,
+            Self::Rolling => Self::Parallel,
+        }
+    }
+}
+
+/// Choices made in the bulk action menu
+#[derive(Debug, Clone, Copy)]
+pub struct BulkMenu {
+    /// Index into [`ControlAction::ALL`]
+    pub selected_action: usize,
+    pub mode: BulkMode,
+    /// Targets per batch in rolling mode
+    pub batch_size: usize,
+}
+
+impl BulkMenu {
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            selected_action: 0,
+            mode: BulkMode::Rolling,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    pub fn action(&self) -> ControlAction {
+        ControlAction::ALL[self.selected_action]
+    }
+
+    pub fn select_next_action(&mut self) {
+        if self.selected_action + 1 < ControlAction::ALL.len() {
+            self.selected_action += 1;
+        }
+    }
+
+    pub fn select_prev_action(&mut self) {
+        self.selected_action = self.selected_action.saturating_sub(1);
+    }
+
+    pub fn grow_batch(&mut self) {
+        self.batch_size += 1;
+    }
+
+    pub fn shrink_batch(&mut self) {
+        self.batch_size = self.batch_size.saturating_sub(1).max(1);
+    }
+}
+
+/// Progress of a bulk action on one target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetStatus {
+    /// Waiting for an earlier batch
+    Queued,
+    /// Connecting and sending the command
+    Sending,
+    /// Command accepted, waiting for the daemon to reach its new state
+    Settling,
+    Succeeded(String),
+    Failed(String),
+    /// Not attempted because an earlier batch failed
+    Skipped,
+    /// Not finished because the operation was cancelled
+    Cancelled,
+}
+
+impl TargetStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Queued | Self::Sending | Self::Settling)
+    }
+}
+
+/// A bulk action and the progress of each of its targets
+#[derive(Debug, Clone)]
+pub struct BulkOperation {
+    pub action: ControlAction,
+    pub mode: BulkMode,
+    pub batch_size: usize,
+    /// Status of each target, keyed by fleet index
+    pub targets: BTreeMap<usize, TargetStatus>,
+    /// Whether the runner is done
+    pub finished: bool,
+}
+
+impl BulkOperation {
+    pub fn new(menu: &BulkMenu, targets: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            action: menu.action(),
+            mode: menu.mode,
+            batch_size: menu.batch_size,
+            targets: targets
+                .into_iter()
+                .map(|index| (index, TargetStatus::Queued))
+                .collect(),
+            finished: false,
+        }
+    }
+
+    /// Record progress reported by the runner
+    pub fn update(&mut self, index: usize, status: TargetStatus) {
+        // Progress may still be queued after a cancellation
+        if self.finished {
+            return;
+        }
+        if let Some(target) = self.targets.get_mut(&index) {
+            *target = status;
+        }
+    }
+
+    /// Mark the operation as done, cancelling targets still in progress
+    pub fn finish(&mut self) {
+        self.finished = true;
+        for status in self.targets.values_mut() {
+            if !status.is_finished() {
+                *status = TargetStatus::Cancelled;
+            }
+        }
+    }
+
+    /// One-line description of the outcome so far
+    pub fn summary(&self) -> String {
+        let count = |f: fn(&TargetStatus) -> bool| self.targets.values().filter(|s| f(s)).count();
+        let done = count(TargetStatus::is_finished);
+        let mut parts = vec![format!(
+            "{} succeeded",
+            count(|s| matches!(s, TargetStatus::Succeeded(_)))
+        )];
+        for (label, n) in [
+            ("failed", count(|s| matches!(s, TargetStatus::Failed(_)))),
+            ("skipped", count(|s| *s == TargetStatus::Skipped)),
+            ("cancelled", count(|s| *s == TargetStatus::Cancelled)),
+        ] {
+            if n > 0 {
+                parts.push(format!("{} {}", n, label));
+            }
+        }
+        format!(
+            "{} {}/{}: {}",
+            self.action.label(),
+            done,
+            self.targets.len(),
+            parts.join(", ")
+        )
+    }
+}
+
+/// Background task running a bulk action.
+///
+/// Each target gets its own client, separate from the fleet poller. The task
+/// is aborted when the runner is dropped.
+pub struct BulkRunner {
+    handle: JoinHandle<()>,
+}
+
+impl BulkRunner {
+    /// Start sending `action` to the targets, given as fleet index and settings
+    pub fn spawn(
+        action: ControlAction,
+        targets: Vec<(usize, Settings)>,
+        batch_size: usize,
+        settle_timeout: Duration,
+        events: mpsc::UnboundedSender<Event>,
+    ) -> Self {
+        let handle = tokio::spawn(async move {
+            let mut batches = targets.chunks(batch_size.max(1));
+            for batch in batches.by_ref() {
+                let results = join_all(batch.iter().map(|(index, settings)| {
+                    run_target(*index, settings, action, settle_timeout, &events)
+                }))
+                .await;
+                if results.contains(&false) {
+                    break;
+                }
+            }
+            for (index, _) in batches.flatten() {
+                let _ = events.send(Event::BulkProgress {
+                    index: *index,
+                    status: TargetStatus::Skipped,
+                });
+            }
+            let _ = events.send(Event::BulkFinished);
+        });
+        Self { handle }
+    }
+}
+
+impl Drop for BulkRunner {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Send the action to one daemon and wait for it to settle.
+///
+/// Returns whether it succeeded.
+async fn run_target(
+    index: usize,
+    settings: &Settings,
+    action: ControlAction,
+    settle_timeout: Duration,
+    events: &mpsc::UnboundedSender<Event>,
+) -> bool {
+    let report = |status: TargetStatus| {
+        let _ = events.send(Event::BulkProgress { index, status });
+    };
+    report(TargetStatus::Sending);
+
+    let mut client = DaemonClient::with_options(settings.address.clone(), settings.client.clone());
+    let auditor = Auditor::new(settings, Origin::Bulk);
+    let status = match send(&mut client, action, &auditor, events).await {
+        Ok((message, before)) => {
+            report(TargetStatus::Settling);
+            match settle(&mut client, action, before.as_ref(), settle_timeout).await {
+                Ok(()) => TargetStatus::Succeeded(message),
+                Err(e) => TargetStatus::Failed(e),
+            }
+        }
+        Err(e) => TargetStatus::Failed(e),
+    };
+    let succeeded = matches!(status, TargetStatus::Succeeded(_));
+    report(status);
+    succeeded
+}
+
+/// Connect and send the command, returning the daemon's response message and
+/// the status it reported just before, if it could be read
+async fn send(
+    client: &mut DaemonClient,
+    action: ControlAction,
+    auditor: &Auditor,
+    events: &mpsc::UnboundedSender<Event>,
+) -> Result<(String, Option<StatusResponse>), String> {
+    client.connect().await.map_err(|e| e.to_string())?;
+    let before = client.get_status().await.ok();
+    let started = Instant::now();
+    let result = client
+        .control(action.to_command())
+        .await
+        .map_err(|e| CallError::from(e).to_string());
+    if let Err(e) = auditor.record(
+        action,
+        result.as_ref().map_err(Clone::clone),
+        started.elapsed(),
+    ) {
+        let _ = events.send(Event::AuditFailed(e));
+    }
+    let response = result?;
+    if response.success {
+        Ok((response.message, before))
+    } else {
+        Err(response.message)
+    }
+}
+
+/// Wait until the daemon reports the state `action` leads to.
+///
+/// A daemon that was already in that state before the command, as it is for
+/// a restart, must first be seen leaving it or report a lower uptime than
+/// `before`, so a command the daemon has yet to act on is not taken as done.
+/// A reload keeps the daemon RUNNING, so it is settled once the daemon
+/// reports RUNNING. Failed status checks are retried, as a restarting daemon
+/// may briefly be unreachable.
+async fn settle(
+    client: &mut DaemonClient,
+    action: ControlAction,
+    before: Option<&StatusResponse>,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let target = action.settled_state();
+    let state_of =
+        |status: &StatusResponse| DaemonState::try_from(status.state).unwrap_or_default();
+    let mut left =
+        action == ControlAction::Reload || before.is_some_and(|status| state_of(status) != target);
+    loop {
+        tokio::time::sleep(SETTLE_POLL_INTERVAL).await;
+        if !client.is_connected() {
+            let _ = client.connect().await;
+        }
+        if let Ok(status) = client.get_status().await {
+            let state = state_of(&status);
+            left |= state != target
+                || before.is_some_and(|before| status.uptime_seconds < before.uptime_seconds);
+            match state {
+                state if state == target && left => return Ok(()),
+                DaemonState::Error => return Err("daemon entered the error state".to_string()),
+                _ => {}
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!(
+                "timed out waiting for {}",
+                target.label().to_uppercase()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+
+    use daemon_controller::mock::{self, MockConfig, MockDaemon};
+
+    use super::*;
+
+    /// Serve a mock daemon that waits `control_delay` before acting on a
+    /// command, returning a client connected to it
+    async fn slow_daemon(control_delay: Duration) -> (DaemonClient, JoinHandle<()>) {
+        let addr: SocketAddr = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("pick a free port");
+        let daemon = MockDaemon::new(MockConfig {
+            control_delay,
+            ..MockConfig::default()
+        });
+        let server = tokio::spawn(async move {
+            mock::serve(addr, daemon, Duration::ZERO, None)
+                .await
+                .expect("mock daemon failed");
+        });
+        let mut client = DaemonClient::new(format!("http://{}", addr));
+        for _ in 0..100 {
+            if client.connect().await.is_ok() {
+                return (client, server);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("mock daemon did not start");
+    }
+
+    #[tokio::test]
+    async fn slow_restart_settles_once_running_again() {
+        let (mut client, server) = slow_daemon(Duration::from_secs(1)).await;
+        let before = client.get_status().await.expect("status");
+        let response = client
+            .control(ControlAction::Restart.to_command())
+            .await
+            .expect("control");
+        assert!(response.success);
+        let sent = Instant::now();
+
+        settle(
+            &mut client,
+            ControlAction::Restart,
+            Some(&before),
+            Duration::from_secs(10),
+        )
+        .await
+        .expect("restart settles");
+        // A second before the restart begins, then a second each in
+        // STOPPING and STARTING
+        assert!(sent.elapsed() >= Duration::from_secs(3));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn restart_not_yet_begun_is_not_settled() {
+        let (mut client, server) = slow_daemon(Duration::from_secs(30)).await;
+        let before = client.get_status().await.expect("status");
+        client
+            .control(ControlAction::Restart.to_command())
+            .await
+            .expect("control");
+
+        let result = settle(
+            &mut client,
+            ControlAction::Restart,
+            Some(&before),
+            Duration::from_secs(1),
+        )
+        .await;
+        assert_eq!(result, Err("timed out waiting for RUNNING".to_string()));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn reload_settles_while_running() {
+        let (mut client, server) = slow_daemon(Duration::ZERO).await;
+        let before = client.get_status().await.expect("status");
+        let response = client
+            .control(ControlAction::Reload.to_command())
+            .await
+            .expect("control");
+        assert_eq!(response.message, "Configuration reloaded");
+
+        let status = client.get_status().await.expect("status");
+        assert_eq!(status.state(), DaemonState::Running);
+        assert!(status.uptime_seconds >= before.uptime_seconds);
+        settle(
+            &mut client,
+            ControlAction::Reload,
+            Some(&before),
+            Duration::from_secs(1),
+        )
+        .await
+        .expect("reload settles");
+        server.abort();
+    }
+}
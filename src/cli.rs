@@ -0,0 +1,620 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::alerts::{parse_duration, AlertRule};
+use crate::app::{log_time, ControlAction};
+use crate::audit::{self, AuditQuery, AuditRecord, Auditor, Origin};
+use crate::config::Settings;
+use crate::events::CallError;
+use crate::log_export::ExportFormat;
+use daemon_controller::grpc::{
+    daemon::{ControlResponse, DaemonState, LogEntry, LogLevel, MetricsResponse, StatusResponse},
+    DaemonClient, LogStreamEvent,
+};
+
+/// Historical entries printed by `logs` unless configured otherwise
+const DEFAULT_TAIL_LINES: u32 = 100;
+
+/// How long a quiet stream is waited on, once entries have arrived, before
+/// `logs` without `--follow` exits with fewer than the requested tail
+const TAIL_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Exit code when the daemon is reachable but not running, or rejected a command
+const EXIT_DAEMON_FAILURE: u8 = 1;
+
+/// Exit code for invalid arguments or configuration
+pub const EXIT_USAGE: u8 = 2;
+
+/// Exit code when the daemon could not be reached
+const EXIT_UNREACHABLE: u8 = 3;
+
+/// Exit code when an RPC failed after connecting
+const EXIT_RPC_FAILED: u8 = 4;
+
+/// Exit code when the output could not be written
+const EXIT_OUTPUT_FAILED: u8 = 5;
+
+/// Monitor and control a daemon via gRPC.
+///
+/// Launches the interactive dashboard when no subcommand is given.
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about,
+    after_help = "Exit codes: 0 success, 1 daemon not running or command rejected, \
+                  2 usage error, 3 daemon unreachable, 4 RPC failed"
+)]
+pub struct Cli {
+    /// Daemon gRPC address for the dashboard (same as --address)
+    #[arg(value_name = "ADDRESS")]
+    pub positional_address: Option<String>,
+
+    /// Daemon gRPC address: http://host:port, https://host:port or
+    /// unix:///path/to/socket
+    #[arg(short, long, global = true)]
+    pub address: Option<String>,
+
+    /// Configuration file
+    /// [default: $XDG_CONFIG_HOME/daemon-controller/config.toml]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Profile from the configuration file
+    #[arg(short, long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Time allowed to connect, e.g. "5s"
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub connect_timeout: Option<Duration>,
+
+    /// Time allowed for each request, e.g. "10s"
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub request_timeout: Option<Duration>,
+
+    /// PEM bundle of CAs to trust instead of the system roots
+    #[arg(long, global = true, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// PEM client certificate for mutual TLS
+    #[arg(long, global = true, value_name = "PATH", requires = "client_key")]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM private key for --client-cert
+    #[arg(long, global = true, value_name = "PATH", requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+
+    /// Name to verify the server certificate against instead of the address host
+    #[arg(long, global = true, value_name = "NAME")]
+    pub tls_server_name: Option<String>,
+
+    /// Accept any server certificate (development only)
+    #[arg(long, global = true)]
+    pub insecure_skip_verify: bool,
+
+    /// File holding the bearer token, re-read when it changes
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "token_env")]
+    pub token_file: Option<PathBuf>,
+
+    /// Environment variable holding the bearer token
+    /// [default: DAEMON_CONTROLLER_TOKEN]
+    #[arg(long, global = true, value_name = "VAR")]
+    pub token_env: Option<String>,
+
+    /// Extra metadata header sent with every request (repeatable)
+    #[arg(long, global = true, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub metadata: Vec<(String, String)>,
+
+    /// Start with an overview of the daemons in the configuration file
+    #[arg(long, conflicts_with_all = ["address", "positional_address", "profile"])]
+    pub fleet: bool,
+
+    /// Interval between dashboard refreshes, e.g. "1s"
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub poll_interval: Option<Duration>,
+
+    /// Output format for subcommands
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Human)]
+    pub output: OutputFormat,
+
+    /// Alert rule for the dashboard, e.g. "cpu_usage_percent > 90 for 30s"
+    /// (repeatable; replaces the default rules)
+    #[arg(long = "alert", value_name = "RULE")]
+    pub alerts: Vec<AlertRule>,
+
+    /// How the dashboard signals a newly fired alert
+    #[arg(long, value_enum, default_value_t = AlertNotify::Bell)]
+    pub alert_notify: AlertNotify,
+
+    /// Write every buffered log line to PATH when the dashboard exits; the
+    /// export keys write here too instead of a timestamped file
+    #[arg(long, value_name = "PATH")]
+    pub export_logs: Option<PathBuf>,
+
+    /// Format of exported logs [default: from the file extension, or text]
+    #[arg(long, value_enum)]
+    pub export_format: Option<ExportFormat>,
+
+    /// Save streamed daemon logs to PATH as JSON lines, rotated as set in the
+    /// profile's log_file table [default: the profile's log_file path]
+    #[arg(long, value_name = "PATH", conflicts_with = "fleet")]
+    pub log_file: Option<PathBuf>,
+
+    /// Browse a saved log file, which may be gzip-compressed, instead of
+    /// connecting to a daemon
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["fleet", "address", "positional_address", "log_file"]
+    )]
+    pub view_logs: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", s))
+}
+
+/// Headless subcommands for scripting
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Print the daemon status (exits 1 unless the daemon is running)
+    Status,
+    /// Print current daemon metrics
+    Metrics,
+    /// Send a control command to the daemon
+    Control {
+        #[arg(value_enum)]
+        action: ControlArg,
+    },
+    /// Print daemon logs
+    Logs {
+        /// Keep streaming new entries until interrupted
+        #[arg(short, long)]
+        follow: bool,
+        /// Minimum level of entries to print
+        /// [default: the profile's log_min_level, or info]
+        #[arg(long, value_enum)]
+        min_level: Option<LevelArg>,
+        /// Number of historical entries to print first
+        /// [default: the profile's tail_lines, or 100]
+        #[arg(long)]
+        tail: Option<u32>,
+    },
+    /// Print recorded control commands, oldest first
+    Audit {
+        /// Only commands sent to daemons whose profile or address contains TEXT
+        #[arg(long, value_name = "TEXT")]
+        daemon: Option<String>,
+        /// Only this control command
+        #[arg(long, value_enum)]
+        command: Option<ControlArg>,
+        /// Only commands sent within this long, e.g. "24h"
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        since: Option<Duration>,
+        /// Only commands that failed
+        #[arg(long)]
+        failed: bool,
+        /// Number of most recent matching records to print
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+/// Output format for subcommand results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+/// Terminal notification for fired alerts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AlertNotify {
+    /// Only show alerts in the Alerts panel
+    None,
+    /// Ring the terminal bell
+    Bell,
+    /// Send an OSC 9 desktop notification
+    Osc9,
+    /// Ring the bell and send an OSC 9 notification
+    Both,
+}
+
+/// Control action accepted on the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ControlArg {
+    Start,
+    Stop,
+    Restart,
+    Reload,
+}
+
+impl From<ControlArg> for ControlAction {
+    fn from(arg: ControlArg) -> Self {
+        match arg {
+            ControlArg::Start => ControlAction::Start,
+            ControlArg::Stop => ControlAction::Stop,
+            ControlArg::Restart => ControlAction::Restart,
+            ControlArg::Reload => ControlAction::Reload,
+        }
+    }
+}
+
+/// Log level accepted on the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LevelArg {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LevelArg> for LogLevel {
+    fn from(arg: LevelArg) -> Self {
+        match arg {
+            LevelArg::Debug => LogLevel::Debug,
+            LevelArg::Info => LogLevel::Info,
+            LevelArg::Warn => LogLevel::Warn,
+            LevelArg::Error => LogLevel::Error,
+        }
+    }
+}
+
+/// Run a headless subcommand and return the process exit code
+pub async fn run(command: CliCommand, settings: Settings, output: OutputFormat) -> ExitCode {
+    // The audit file is local, so there is no daemon to connect to
+    if let CliCommand::Audit {
+        daemon,
+        command,
+        since,
+        failed,
+        limit,
+    } = command
+    {
+        let query = AuditQuery {
+            daemon,
+            command: command.map(ControlAction::from),
+            since,
+            failed,
+            limit,
+        };
+        return audit(&settings, query, output);
+    }
+
+    let auditor = Auditor::new(&settings, Origin::Cli);
+    let mut client = DaemonClient::with_options(settings.address.clone(), settings.client);
+    if let Err(e) = client.connect().await {
+        eprintln!("Failed to connect to {}: {}", settings.address, e);
+        return ExitCode::from(EXIT_UNREACHABLE);
+    }
+
+    match command {
+        CliCommand::Status => status(&mut client, output).await,
+        CliCommand::Metrics => metrics(&mut client, output).await,
+        CliCommand::Control { action } => {
+            control(&mut client, action.into(), &auditor, output).await
+        }
+        CliCommand::Logs {
+            follow,
+            min_level,
+            tail,
+        } => {
+            let min_level = min_level
+                .map(LogLevel::from)
+                .or(settings.log_min_level)
+                .unwrap_or(LogLevel::Info);
+            let tail = tail.or(settings.tail_lines).unwrap_or(DEFAULT_TAIL_LINES);
+            logs(&mut client, follow, min_level, tail, output).await
+        }
+        CliCommand::Audit { .. } => unreachable!("handled before connecting"),
+    }
+}
+
+async fn status(client: &mut DaemonClient, output: OutputFormat) -> ExitCode {
+    let status = match client.get_status().await {
+        Ok(status) => status,
+        Err(e) => return rpc_failed("get status", e),
+    };
+    let state = DaemonState::try_from(status.state).unwrap_or_default();
+    if let Err(e) = print_status(&status, state, output) {
+        return write_failed(e);
+    }
+
+    if state == DaemonState::Running {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_DAEMON_FAILURE)
+    }
+}
+
+async fn metrics(client: &mut DaemonClient, output: OutputFormat) -> ExitCode {
+    let metrics = match client.get_metrics().await {
+        Ok(metrics) => metrics,
+        Err(e) => return rpc_failed("get metrics", e),
+    };
+    match print_metrics(&metrics, output) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => write_failed(e),
+    }
+}
+
+async fn control(
+    client: &mut DaemonClient,
+    action: ControlAction,
+    auditor: &Auditor,
+    output: OutputFormat,
+) -> ExitCode {
+    let started = Instant::now();
+    let result = client
+        .control(action.to_command())
+        .await
+        .map_err(CallError::from);
+    let outcome = result.as_ref().map_err(CallError::to_string);
+    if let Err(e) = auditor.record(action, outcome, started.elapsed()) {
+        eprintln!("Warning: failed to write audit record: {}", e);
+    }
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => return rpc_failed(action.label(), e),
+    };
+    if let Err(e) = print_control(action, &response, output) {
+        return write_failed(e);
+    }
+
+    if response.success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_DAEMON_FAILURE)
+    }
+}
+
+async fn logs(
+    client: &mut DaemonClient,
+    follow: bool,
+    min_level: LogLevel,
+    tail: u32,
+    output: OutputFormat,
+) -> ExitCode {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if let Err(e) = client.subscribe_logs(min_level, tail, tx) {
+        return rpc_failed("stream logs", e);
+    }
+    if !follow && tail == 0 {
+        return ExitCode::SUCCESS;
+    }
+
+    let mut printed = 0;
+    loop {
+        let event = if follow || printed == 0 {
+            rx.recv().await
+        } else {
+            // The stream never ends on its own and the daemon may hold fewer
+            // entries than asked for, so a quiet stream also ends the tail
+            tokio::time::timeout(TAIL_IDLE_TIMEOUT, rx.recv())
+                .await
+                .ok()
+                .flatten()
+        };
+
+        match event {
+            Some(LogStreamEvent::Entry(entry)) => {
+                if let Err(e) = print_log(&entry, output) {
+                    return write_failed(e);
+                }
+                printed += 1;
+                if !follow && printed >= tail {
+                    return ExitCode::SUCCESS;
+                }
+            }
+            Some(LogStreamEvent::Closed) => {
+                if !follow {
+                    return ExitCode::SUCCESS;
+                }
+                eprintln!("Log stream closed by daemon - resubscribing");
+            }
+            Some(LogStreamEvent::Dropped(reason)) => {
+                if !follow {
+                    eprintln!("{}", reason);
+                    return ExitCode::from(EXIT_RPC_FAILED);
+                }
+                eprintln!("{} - resubscribing", reason);
+            }
+            None => return ExitCode::SUCCESS,
+        }
+    }
+}
+
+fn print_status(
+    status: &StatusResponse,
+    state: DaemonState,
+    output: OutputFormat,
+) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Human => {
+            writeln!(out, "State:   {}", state.label())?;
+            writeln!(out, "Version: {}", status.version)?;
+            writeln!(out, "Uptime:  {}s", status.uptime_seconds)?;
+            writeln!(out, "Message: {}", status.message)
+        }
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "state": state.label(),
+                "version": status.version,
+                "uptime_seconds": status.uptime_seconds,
+                "message": status.message,
+            })
+        ),
+    }
+}
+
+fn print_metrics(metrics: &MetricsResponse, output: OutputFormat) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Human => {
+            writeln!(out, "CPU:         {:.1}%", metrics.cpu_usage_percent)?;
+            writeln!(
+                out,
+                "Memory:      {} / {} bytes",
+                metrics.memory_bytes, metrics.memory_limit_bytes
+            )?;
+            writeln!(out, "Connections: {}", metrics.connections_active)?;
+            writeln!(out, "Requests:    {}", metrics.requests_total)?;
+            writeln!(out, "Errors:      {}", metrics.errors_total)
+        }
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "cpu_usage_percent": metrics.cpu_usage_percent,
+                "memory_bytes": metrics.memory_bytes,
+                "memory_limit_bytes": metrics.memory_limit_bytes,
+                "connections_active": metrics.connections_active,
+                "requests_total": metrics.requests_total,
+                "errors_total": metrics.errors_total,
+            })
+        ),
+    }
+}
+
+fn print_control(
+    action: ControlAction,
+    response: &ControlResponse,
+    output: OutputFormat,
+) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Human => {
+            let outcome = if response.success {
+                "succeeded"
+            } else {
+                "failed"
+            };
+            writeln!(out, "{} {}: {}", action.label(), outcome, response.message)
+        }
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "command": action.label(),
+                "success": response.success,
+                "message": response.message,
+            })
+        ),
+    }
+}
+
+fn print_log(entry: &LogEntry, output: OutputFormat) -> io::Result<()> {
+    let level = LogLevel::try_from(entry.level)
+        .map(LogLevel::label)
+        .unwrap_or("?");
+    let timestamp = log_time(entry.timestamp)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default();
+
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Human => writeln!(
+            out,
+            "{} {:<5} [{}] {}",
+            timestamp, level, entry.source, entry.message
+        ),
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "timestamp": timestamp,
+                "timestamp_ms": entry.timestamp,
+                "level": level,
+                "source": entry.source,
+                "message": entry.message,
+            })
+        ),
+    }
+}
+
+fn audit(settings: &Settings, query: AuditQuery, output: OutputFormat) -> ExitCode {
+    let Some(path) = &settings.audit_file else {
+        eprintln!("No audit file location known; set audit_file in the configuration file");
+        return ExitCode::from(EXIT_USAGE);
+    };
+    let records = match audit::read_records(path) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Failed to read the audit file: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    for record in query.select(&records) {
+        if let Err(e) = print_audit_record(record, output) {
+            return write_failed(e);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn print_audit_record(record: &AuditRecord, output: OutputFormat) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Human => {
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|_| record.timestamp.clone());
+            writeln!(
+                out,
+                "{} {}@{} {} -> {}: {} {} ({}ms) {}",
+                timestamp,
+                record.user,
+                record.host,
+                record.origin.label(),
+                record.daemon(),
+                record.command,
+                if record.success {
+                    "succeeded"
+                } else {
+                    "failed"
+                },
+                record.latency_ms,
+                record.message
+            )
+        }
+        OutputFormat::Json => match serde_json::to_string(record) {
+            Ok(line) => writeln!(out, "{}", line),
+            Err(e) => {
+                eprintln!("Failed to encode audit record: {}", e);
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Exit code for output that could not be written. A reader closing the pipe
+/// early, as `head` does, is not an error.
+fn write_failed(error: io::Error) -> ExitCode {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("Failed to write output: {}", error);
+        ExitCode::from(EXIT_OUTPUT_FAILED)
+    }
+}
+
+fn rpc_failed(what: &str, error: impl Into<CallError>) -> ExitCode {
+    eprintln!("Failed to {}: {}", what.to_lowercase(), error.into());
+    ExitCode::from(EXIT_RPC_FAILED)
+}
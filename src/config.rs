@@ -0,0 +1,1033 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tonic::transport::Uri;
+
+use crate::alerts::{parse_duration, AlertRule};
+use crate::app::{ConfirmPolicy, Confirmation};
+use crate::cli::Cli;
+use crate::keymap::{Action, Chord, Context, Keymap};
+use crate::log_buffer;
+use crate::log_file::{self, LogFileOptions};
+use daemon_controller::grpc::{
+    daemon::LogLevel, socket_path, AuthOptions, ClientOptions, TlsOptions, TokenSource,
+};
+
+/// Default daemon address
+pub const DEFAULT_DAEMON_ADDRESS: &str = "http://127.0.0.1:50051";
+
+/// Default interval between status/metrics refreshes in the dashboard
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Default interval between polls of each daemon in the fleet view
+const DEFAULT_FLEET_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default time a bulk action waits for each daemon to reach its new state
+const DEFAULT_SETTLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Directory under the config dir holding this application's files
+const APP_DIR: &str = "daemon-controller";
+
+/// Name of the configuration file
+const CONFIG_FILE: &str = "config.toml";
+
+/// Audit file name under the application's data directory
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Environment variable holding the daemon token when none is configured
+const TOKEN_ENV_VAR: &str = "DAEMON_CONTROLLER_TOKEN";
+
+/// Contents of the configuration file.
+///
+/// ```toml
+/// default_profile = "local"
+/// audit_file = "/var/log/daemon-controller/audit.jsonl"
+///
+/// [profiles.local]
+/// address = "http://127.0.0.1:50051"
+///
+/// [profiles.prod]
+/// address = "http://daemon.example.com:50051"
+/// connect_timeout = "2s"
+/// request_timeout = "5s"
+/// poll_interval = "1s"
+/// log_min_level = "info"
+/// tail_lines = 500
+/// log_buffer_lines = 50000
+/// alerts = ["error_rate > 1 for 1m", "state == ERROR"]
+///
+/// [profiles.prod.tls]
+/// ca_cert = "certs/ca.pem"
+/// client_cert = "certs/client.pem"
+/// client_key = "certs/client-key.pem"
+/// server_name = "daemon.internal"
+///
+/// [profiles.prod.auth]
+/// token_file = "/run/secrets/daemon-token"
+/// metadata = { x-tenant = "ops" }
+///
+/// [profiles.prod.confirm]
+/// stop = "type_name"
+/// restart = "prompt"
+/// reload = "prompt"
+///
+/// [profiles.prod.log_file]
+/// path = "logs/prod.jsonl"
+/// max_size = "50MB"
+/// max_age = "24h"
+/// keep = 7
+/// compress = true
+///
+/// [keys]
+/// restart = "ctrl-r"
+/// quit = ["q", "ctrl-c"]
+/// top = "g g"
+///
+/// [keys.logs]
+/// search = "ctrl-f"
+///
+/// [fleet]
+/// profiles = ["local", "prod"]
+/// poll_interval = "5s"
+/// batch_size = 2
+/// settle_timeout = "2m"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// Profile used when `--profile` is not given
+    default_profile: Option<String>,
+    /// Where control commands are recorded, resolved like TLS paths
+    audit_file: Option<PathBuf>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+    fleet: Option<FleetProfile>,
+    /// Key bindings replacing the defaults
+    #[serde(default)]
+    keys: BTreeMap<String, KeysEntry>,
+}
+
+/// Entry of the `[keys]` table: the keys of an action wherever it applies,
+/// or a table of actions whose keys are replaced in one context only
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeysEntry {
+    Keys(KeySpec),
+    Context(BTreeMap<String, KeySpec>),
+}
+
+/// Keys bound to an action: a key or chord such as `"ctrl-x r"`, or a list
+/// of them. An empty list leaves the action unbound.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn chords(&self) -> Result<Vec<Chord>, String> {
+        match self {
+            Self::One(chord) => Ok(vec![chord.parse()?]),
+            Self::Many(chords) => chords.iter().map(|chord| chord.parse()).collect(),
+        }
+    }
+}
+
+/// Daemons shown by `--fleet`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FleetProfile {
+    /// Profiles to include, in display order; all profiles when unset
+    profiles: Option<Vec<String>>,
+    poll_interval: Option<String>,
+    /// Initial batch size of rolling bulk actions
+    batch_size: Option<usize>,
+    /// Time a bulk action waits for each daemon to settle
+    settle_timeout: Option<String>,
+}
+
+/// Named daemon settings; unset fields fall back to the defaults
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    address: Option<String>,
+    connect_timeout: Option<String>,
+    request_timeout: Option<String>,
+    poll_interval: Option<String>,
+    log_min_level: Option<String>,
+    tail_lines: Option<u32>,
+    /// Log entries kept in the logs panel
+    log_buffer_lines: Option<usize>,
+    alerts: Option<Vec<String>>,
+    tls: Option<TlsProfile>,
+    auth: Option<AuthProfile>,
+    confirm: Option<ConfirmProfile>,
+    log_file: Option<LogFileProfile>,
+}
+
+/// TLS settings of a profile, used with `https://` addresses.
+///
+/// Relative paths are resolved against the config file's directory.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TlsProfile {
+    ca_cert: Option<PathBuf>,
+    client_cert: Option<PathBuf>,
+    client_key: Option<PathBuf>,
+    server_name: Option<String>,
+    #[serde(default)]
+    insecure_skip_verify: bool,
+}
+
+impl TlsProfile {
+    fn resolve(self, base: Option<&Path>) -> TlsOptions {
+        let resolve = |path: Option<PathBuf>| match (path, base) {
+            (Some(path), Some(base)) if path.is_relative() => Some(base.join(path)),
+            (path, _) => path,
+        };
+        TlsOptions {
+            ca_cert: resolve(self.ca_cert),
+            client_cert: resolve(self.client_cert),
+            client_key: resolve(self.client_key),
+            server_name: self.server_name,
+            insecure_skip_verify: self.insecure_skip_verify,
+        }
+    }
+}
+
+/// Authentication settings of a profile.
+///
+/// At most one of `token`, `token_env` and `token_file` may be set.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthProfile {
+    token: Option<String>,
+    /// Environment variable holding the token
+    token_env: Option<String>,
+    /// File holding the token, resolved like TLS paths
+    token_file: Option<PathBuf>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+impl AuthProfile {
+    fn resolve(self, base: Option<&Path>) -> Result<AuthOptions, String> {
+        let token = match (self.token, self.token_env, self.token_file) {
+            (Some(token), None, None) => Some(TokenSource::Value(token)),
+            (None, Some(var), None) => Some(token_from_env(&var)?),
+            (None, None, Some(path)) => Some(TokenSource::File(match base {
+                Some(base) if path.is_relative() => base.join(path),
+                _ => path,
+            })),
+            (None, None, None) => None,
+            _ => return Err("only one of token, token_env and token_file may be set".to_string()),
+        };
+        Ok(AuthOptions {
+            token,
+            metadata: self.metadata.into_iter().collect(),
+        })
+    }
+}
+
+/// Confirmation of each control action: `"none"`, `"prompt"` or
+/// `"type_name"`.
+///
+/// Unset actions keep their default, which prompts for Stop and Restart.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfirmProfile {
+    start: Option<String>,
+    stop: Option<String>,
+    restart: Option<String>,
+    reload: Option<String>,
+}
+
+impl ConfirmProfile {
+    fn resolve(self) -> Result<ConfirmPolicy, String> {
+        let defaults = ConfirmPolicy::default();
+        let field = |name: &str, value: Option<String>, default| {
+            value
+                .map(|v| parse_confirmation(&v))
+                .transpose()
+                .map(|v| v.unwrap_or(default))
+                .map_err(|e| format!("{}: {}", name, e))
+        };
+        Ok(ConfirmPolicy {
+            start: field("start", self.start, defaults.start)?,
+            stop: field("stop", self.stop, defaults.stop)?,
+            restart: field("restart", self.restart, defaults.restart)?,
+            reload: field("reload", self.reload, defaults.reload)?,
+        })
+    }
+}
+
+/// File the dashboard saves streamed daemon logs to.
+///
+/// The path is resolved like TLS paths. Rotated files are named after it
+/// with `.1`, `.2`, ... appended, most recent first, plus `.gz` when
+/// compressed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LogFileProfile {
+    path: PathBuf,
+    /// Size past which the file is rotated, e.g. "10MB"; "0" never rotates
+    /// by size
+    max_size: Option<String>,
+    /// Age past which the file is rotated, e.g. "24h"
+    max_age: Option<String>,
+    /// Rotated files kept
+    keep: Option<usize>,
+    compress: Option<bool>,
+}
+
+impl LogFileProfile {
+    fn resolve(self, base: Option<&Path>) -> Result<LogFileOptions, String> {
+        let max_size = match self.max_size {
+            Some(size) => Some(parse_size(&size).map_err(|e| format!("max_size: {}", e))?)
+                .filter(|&size| size > 0),
+            None => Some(log_file::DEFAULT_MAX_SIZE),
+        };
+        let max_age = self
+            .max_age
+            .map(|age| duration_field("max_age", Some(age), Duration::ZERO))
+            .transpose()?;
+        Ok(LogFileOptions {
+            path: match base {
+                Some(base) if self.path.is_relative() => base.join(self.path),
+                _ => self.path,
+            },
+            max_size,
+            max_age,
+            keep: self.keep.unwrap_or(log_file::DEFAULT_KEEP),
+            compress: self.compress.unwrap_or(true),
+        })
+    }
+}
+
+/// Settings resolved from the defaults, the selected profile and CLI flags
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Name of the selected profile, if any
+    pub profile: Option<String>,
+    pub address: String,
+    pub client: ClientOptions,
+    pub poll_interval: Duration,
+    /// Minimum level of streamed daemon logs, if configured
+    pub log_min_level: Option<LogLevel>,
+    /// Number of historical log lines to request, if configured
+    pub tail_lines: Option<u32>,
+    /// Log entries kept in the logs panel before the oldest are dropped
+    pub log_buffer_lines: usize,
+    /// File streamed daemon logs are saved to, if any
+    pub log_file: Option<LogFileOptions>,
+    /// Alert rules, if configured
+    pub alerts: Option<Vec<AlertRule>>,
+    /// Confirmation required before each control action
+    pub confirm: ConfirmPolicy,
+    /// File control commands are recorded in, if a location is known
+    pub audit_file: Option<PathBuf>,
+    /// Daemons to show in the fleet view, when `--fleet` is given
+    pub fleet: Option<FleetSettings>,
+    /// Key bindings of the dashboard
+    pub keymap: Keymap,
+}
+
+/// Daemons polled by the fleet view
+#[derive(Debug, Clone)]
+pub struct FleetSettings {
+    /// Settings of each daemon, named after its profile
+    pub members: Vec<Settings>,
+    pub poll_interval: Duration,
+    /// Initial batch size of rolling bulk actions
+    pub batch_size: usize,
+    /// Time a bulk action waits for each daemon to settle
+    pub settle_timeout: Duration,
+}
+
+impl Settings {
+    /// Load the configuration file and apply the profile and CLI overrides
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let (path, file) = read_cli_config(cli)?;
+        let origin = config_origin(path.as_deref());
+        let base = path.as_deref().and_then(Path::parent);
+        let keymap =
+            resolve_keymap(&file.keys).map_err(|e| format!("invalid config {}: {}", origin, e))?;
+
+        let audit_file = match &file.audit_file {
+            Some(path) => Some(match base {
+                Some(base) if path.is_relative() => base.join(path),
+                _ => path.clone(),
+            }),
+            None => default_audit_path(),
+        };
+
+        let fleet = if cli.fleet {
+            if cli.command.is_some() {
+                return Err("--fleet only applies to the dashboard".to_string());
+            }
+            let mut fleet = load_fleet(&file, base, &origin, cli)?;
+            for member in &mut fleet.members {
+                member.audit_file = audit_file.clone();
+            }
+            Some(fleet)
+        } else {
+            None
+        };
+
+        let name = cli.profile.clone().or(file.default_profile);
+        let resolved = match &name {
+            Some(name) => {
+                let mut profiles = file.profiles;
+                let available: Vec<String> = profiles.keys().cloned().collect();
+                let profile = profiles.remove(name).ok_or_else(|| {
+                    if available.is_empty() {
+                        format!(
+                            "unknown profile '{}': no profiles defined in {}",
+                            name, origin
+                        )
+                    } else {
+                        format!(
+                            "unknown profile '{}' in {} (available: {})",
+                            name,
+                            origin,
+                            available.join(", ")
+                        )
+                    }
+                })?;
+                Self::from_profile(profile, base)
+                    .map_err(|e| format!("profile '{}' in {}: {}", name, origin, e))?
+            }
+            None => Self::from_profile(Profile::default(), base)?,
+        };
+
+        let mut settings = Self {
+            profile: name,
+            audit_file,
+            fleet,
+            keymap,
+            ..resolved
+        };
+        settings.apply_cli(cli)?;
+        validate_address(&settings.address)?;
+        validate_tls(&settings.address, &settings.client.tls)?;
+        Ok(settings)
+    }
+
+    /// Resolve a profile's values, filling in defaults
+    fn from_profile(profile: Profile, base: Option<&Path>) -> Result<Self, String> {
+        let defaults = ClientOptions::default();
+        let alerts = profile
+            .alerts
+            .map(|rules| {
+                rules
+                    .iter()
+                    .map(|rule| rule.parse())
+                    .collect::<Result<Vec<AlertRule>, _>>()
+            })
+            .transpose()
+            .map_err(|e| format!("alerts: {}", e))?;
+
+        let address = profile
+            .address
+            .unwrap_or_else(|| DEFAULT_DAEMON_ADDRESS.to_string());
+        validate_address(&address)?;
+        let tls = profile.tls.map(|tls| tls.resolve(base)).unwrap_or_default();
+        validate_tls(&address, &tls).map_err(|e| format!("tls: {}", e))?;
+        let auth = profile
+            .auth
+            .map(|auth| auth.resolve(base))
+            .transpose()
+            .map_err(|e| format!("auth: {}", e))?
+            .unwrap_or_default();
+        let confirm = profile
+            .confirm
+            .map(ConfirmProfile::resolve)
+            .transpose()
+            .map_err(|e| format!("confirm: {}", e))?
+            .unwrap_or_default();
+        let log_file = profile
+            .log_file
+            .map(|log_file| log_file.resolve(base))
+            .transpose()
+            .map_err(|e| format!("log_file: {}", e))?;
+        let log_buffer_lines = match profile.log_buffer_lines {
+            Some(0) => return Err("log_buffer_lines: must be greater than zero".to_string()),
+            Some(lines) => lines,
+            None => log_buffer::DEFAULT_CAPACITY,
+        };
+
+        Ok(Self {
+            profile: None,
+            address,
+            client: ClientOptions {
+                connect_timeout: duration_field(
+                    "connect_timeout",
+                    profile.connect_timeout,
+                    defaults.connect_timeout,
+                )?,
+                request_timeout: duration_field(
+                    "request_timeout",
+                    profile.request_timeout,
+                    defaults.request_timeout,
+                )?,
+                tls,
+                auth,
+            },
+            poll_interval: duration_field(
+                "poll_interval",
+                profile.poll_interval,
+                DEFAULT_POLL_INTERVAL,
+            )?,
+            log_min_level: profile
+                .log_min_level
+                .map(|level| parse_log_level(&level))
+                .transpose()?,
+            tail_lines: profile.tail_lines,
+            log_buffer_lines,
+            log_file,
+            alerts,
+            confirm,
+            audit_file: None,
+            fleet: None,
+            keymap: Keymap::default(),
+        })
+    }
+
+    /// Let flags given on the command line take precedence
+    fn apply_cli(&mut self, cli: &Cli) -> Result<(), String> {
+        if let Some(address) = cli.address.clone().or(cli.positional_address.clone()) {
+            self.address = address;
+        }
+        self.apply_cli_options(cli)
+    }
+
+    /// Apply command line flags other than the address
+    fn apply_cli_options(&mut self, cli: &Cli) -> Result<(), String> {
+        if let Some(timeout) = cli.connect_timeout {
+            self.client.connect_timeout = timeout;
+        }
+        if let Some(timeout) = cli.request_timeout {
+            self.client.request_timeout = timeout;
+        }
+        if let Some(interval) = cli.poll_interval {
+            self.poll_interval = interval;
+        }
+        if !cli.alerts.is_empty() {
+            self.alerts = Some(cli.alerts.clone());
+        }
+        if let Some(path) = &cli.log_file {
+            match &mut self.log_file {
+                Some(log_file) => log_file.path = path.clone(),
+                None => self.log_file = Some(LogFileOptions::new(path.clone())),
+            }
+        }
+
+        let tls = &mut self.client.tls;
+        if let Some(path) = &cli.ca_cert {
+            tls.ca_cert = Some(path.clone());
+        }
+        if let Some(path) = &cli.client_cert {
+            tls.client_cert = Some(path.clone());
+        }
+        if let Some(path) = &cli.client_key {
+            tls.client_key = Some(path.clone());
+        }
+        if let Some(name) = &cli.tls_server_name {
+            tls.server_name = Some(name.clone());
+        }
+        if cli.insecure_skip_verify {
+            tls.insecure_skip_verify = true;
+        }
+
+        let auth = &mut self.client.auth;
+        if let Some(path) = &cli.token_file {
+            auth.token = Some(TokenSource::File(path.clone()));
+        } else if let Some(var) = &cli.token_env {
+            auth.token = Some(token_from_env(var)?);
+        } else if auth.token.is_none() {
+            auth.token = std::env::var(TOKEN_ENV_VAR).ok().map(TokenSource::Value);
+        }
+        for (key, value) in &cli.metadata {
+            auth.metadata.retain(|(existing, _)| existing != key);
+            auth.metadata.push((key.clone(), value.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Resolve the fleet's profiles, applying CLI flags other than the address
+/// to each
+fn load_fleet(
+    file: &ConfigFile,
+    base: Option<&Path>,
+    origin: &str,
+    cli: &Cli,
+) -> Result<FleetSettings, String> {
+    let fleet = file.fleet.as_ref();
+    let names: Vec<String> = match fleet.and_then(|fleet| fleet.profiles.clone()) {
+        Some(names) => names,
+        None => file.profiles.keys().cloned().collect(),
+    };
+    if names.is_empty() {
+        return Err(format!("--fleet: no profiles defined in {}", origin));
+    }
+
+    let members = names
+        .into_iter()
+        .map(|name| {
+            let profile = file.profiles.get(&name).ok_or_else(|| {
+                format!(
+                    "fleet: unknown profile '{}' in {} (available: {})",
+                    name,
+                    origin,
+                    file.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })?;
+            let context = |e: String| format!("profile '{}' in {}: {}", name, origin, e);
+            let mut settings = Settings::from_profile(profile.clone(), base).map_err(context)?;
+            settings.apply_cli_options(cli).map_err(context)?;
+            validate_tls(&settings.address, &settings.client.tls).map_err(context)?;
+            settings.profile = Some(name);
+            Ok(settings)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let poll_interval = duration_field(
+        "fleet.poll_interval",
+        fleet.and_then(|fleet| fleet.poll_interval.clone()),
+        DEFAULT_FLEET_POLL_INTERVAL,
+    )?;
+    let settle_timeout = duration_field(
+        "fleet.settle_timeout",
+        fleet.and_then(|fleet| fleet.settle_timeout.clone()),
+        DEFAULT_SETTLE_TIMEOUT,
+    )?;
+    let batch_size = match fleet.and_then(|fleet| fleet.batch_size) {
+        Some(0) => return Err("fleet.batch_size: must be greater than zero".to_string()),
+        Some(size) => size,
+        None => 1,
+    };
+    Ok(FleetSettings {
+        members,
+        poll_interval,
+        batch_size,
+        settle_timeout,
+    })
+}
+
+/// Key bindings from the configuration file, for the log file viewer
+/// which needs nothing else from it
+pub fn load_keymap(cli: &Cli) -> Result<Keymap, String> {
+    let (path, file) = read_cli_config(cli)?;
+    let origin = config_origin(path.as_deref());
+    resolve_keymap(&file.keys).map_err(|e| format!("invalid config {}: {}", origin, e))
+}
+
+/// Replace the default key bindings with those configured, keys given for
+/// one context taking precedence over keys given for every context
+fn resolve_keymap(keys: &BTreeMap<String, KeysEntry>) -> Result<Keymap, String> {
+    let mut keymap = Keymap::default();
+    for (name, entry) in keys {
+        if let KeysEntry::Keys(spec) = entry {
+            rebind(&mut keymap, None, name, spec).map_err(|e| format!("keys.{}: {}", name, e))?;
+        }
+    }
+    for (name, entry) in keys {
+        let KeysEntry::Context(table) = entry else {
+            continue;
+        };
+        let context = Context::from_name(name).ok_or_else(|| {
+            format!(
+                "keys.{}: unknown context (available: {})",
+                name,
+                Context::ALL.map(Context::name).join(", ")
+            )
+        })?;
+        for (action, spec) in table {
+            rebind(&mut keymap, Some(context), action, spec)
+                .map_err(|e| format!("keys.{}.{}: {}", name, action, e))?;
+        }
+    }
+    keymap.check().map_err(|e| format!("keys: {}", e))?;
+    Ok(keymap)
+}
+
+fn rebind(
+    keymap: &mut Keymap,
+    context: Option<Context>,
+    name: &str,
+    spec: &KeySpec,
+) -> Result<(), String> {
+    let action = Action::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown action (available: {})",
+            Action::ALL.map(Action::name).join(", ")
+        )
+    })?;
+    keymap.rebind(context, action, spec.chords()?)
+}
+
+/// Read the configuration file named on the command line, or the default
+/// one if it exists, along with its path
+fn read_cli_config(cli: &Cli) -> Result<(Option<PathBuf>, ConfigFile), String> {
+    match &cli.config {
+        Some(path) => Ok((Some(path.clone()), read_config(path, true)?)),
+        None => match default_config_path() {
+            Some(path) => {
+                let file = read_config(&path, false)?;
+                Ok((Some(path), file))
+            }
+            None => Ok((None, ConfigFile::default())),
+        },
+    }
+}
+
+/// How the configuration file is referred to in errors
+fn config_origin(path: Option<&Path>) -> String {
+    path.map(|p| p.display().to_string())
+        .unwrap_or_else(|| "the configuration file".to_string())
+}
+
+/// Location of the configuration file under the XDG config directory
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join(APP_DIR).join(CONFIG_FILE))
+}
+
+/// Location of the audit file under the XDG data directory
+fn default_audit_path() -> Option<PathBuf> {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))?;
+    Some(data_dir.join(APP_DIR).join(AUDIT_FILE))
+}
+
+/// Read and parse a configuration file.
+///
+/// A missing file is only an error if it was named explicitly.
+fn read_config(path: &Path, required: bool) -> Result<ConfigFile, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !required => {
+            return Ok(ConfigFile::default());
+        }
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    toml::from_str(&contents).map_err(|e| {
+        // Parse errors end with a newline after the source excerpt
+        format!(
+            "invalid config {}: {}",
+            path.display(),
+            e.to_string().trim_end()
+        )
+    })
+}
+
+fn duration_field(
+    field: &str,
+    value: Option<String>,
+    default: Duration,
+) -> Result<Duration, String> {
+    let Some(value) = value else {
+        return Ok(default);
+    };
+    let duration = parse_duration(&value).map_err(|e| format!("{}: {}", field, e))?;
+    if duration.is_zero() {
+        return Err(format!("{}: must be greater than zero", field));
+    }
+    Ok(duration)
+}
+
+/// Parse a size such as "512KB" or "10MB"; a bare number is in bytes
+fn parse_size(s: &str) -> Result<u64, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", s))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        "GB" => 1024 * 1024 * 1024,
+        _ => {
+            return Err(format!(
+                "unknown unit '{}' in size '{}' (use B, KB, MB or GB)",
+                unit, s
+            ))
+        }
+    };
+    amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{}' is too large", s))
+}
+
+fn parse_log_level(s: &str) -> Result<LogLevel, String> {
+    LogLevel::from_label(s).ok_or_else(|| {
+        format!(
+            "log_min_level: unknown level '{}' (expected debug, info, warn or error)",
+            s
+        )
+    })
+}
+
+fn parse_confirmation(s: &str) -> Result<Confirmation, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "none" => Ok(Confirmation::None),
+        "prompt" => Ok(Confirmation::Prompt),
+        "type_name" => Ok(Confirmation::TypeName),
+        _ => Err(format!(
+            "unknown confirmation '{}' (expected none, prompt or type_name)",
+            s
+        )),
+    }
+}
+
+fn validate_address(address: &str) -> Result<(), String> {
+    if let Some(path) = socket_path(address) {
+        if path.as_os_str().is_empty() {
+            return Err(format!(
+                "invalid address '{}': missing socket path",
+                address
+            ));
+        }
+        return Ok(());
+    }
+    let uri: Uri = address
+        .parse()
+        .map_err(|e| format!("invalid address '{}': {}", address, e))?;
+    match uri.scheme_str() {
+        Some("http" | "https") if uri.host().is_some() => Ok(()),
+        _ => Err(format!(
+            "invalid address '{}': expected http://host:port, https://host:port \
+             or unix:///path/to/socket",
+            address
+        )),
+    }
+}
+
+fn token_from_env(var: &str) -> Result<TokenSource, String> {
+    std::env::var(var)
+        .map(TokenSource::Value)
+        .map_err(|e| format!("token environment variable {}: {}", var, e))
+}
+
+fn validate_tls(address: &str, tls: &TlsOptions) -> Result<(), String> {
+    if tls.client_cert.is_some() != tls.client_key.is_some() {
+        return Err("client certificate and key must be given together".to_string());
+    }
+    if tls.is_customized() && !address.starts_with("https://") {
+        return Err(format!(
+            "TLS settings require an https:// address, got '{}'",
+            address
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    /// Load settings from `config` with the given command line arguments,
+    /// naming the file `config.toml` in errors
+    fn load(config: &str, args: &[&str]) -> Result<Settings, String> {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(&path, config).expect("write config");
+        let path = path.to_str().expect("UTF-8 temp dir");
+        let cli = Cli::try_parse_from(
+            ["daemon-controller", "--config", path]
+                .into_iter()
+                .chain(args.iter().copied()),
+        )
+        .expect("valid arguments");
+        Settings::load(&cli).map_err(|e| e.replace(path, "config.toml"))
+    }
+
+    fn load_err(config: &str, args: &[&str]) -> String {
+        load(config, args).expect_err("invalid config")
+    }
+
+    #[test]
+    fn profile_values() {
+        let settings = load(
+            r#"
+            default_profile = "prod"
+
+            [profiles.prod]
+            address = "http://daemon.example.com:50051"
+            connect_timeout = "2s"
+            poll_interval = "1s"
+            log_min_level = "warn"
+            tail_lines = 500
+
+            [profiles.prod.log_file]
+            path = "logs/prod.jsonl"
+            max_size = "10MB"
+            "#,
+            &[],
+        )
+        .expect("valid config");
+        assert_eq!(settings.profile.as_deref(), Some("prod"));
+        assert_eq!(settings.address, "http://daemon.example.com:50051");
+        assert_eq!(settings.client.connect_timeout, Duration::from_secs(2));
+        assert_eq!(settings.poll_interval, Duration::from_secs(1));
+        assert_eq!(settings.log_min_level, Some(LogLevel::Warn));
+        assert_eq!(settings.tail_lines, Some(500));
+        let log_file = settings.log_file.expect("log file");
+        assert!(log_file.path.ends_with("logs/prod.jsonl"));
+        assert_eq!(log_file.max_size, Some(10 * 1024 * 1024));
+    }
+
+    #[test]
+    fn cli_flags_take_precedence() {
+        let config = r#"
+            [profiles.prod]
+            address = "http://daemon.example.com:50051"
+            connect_timeout = "2s"
+            poll_interval = "1s"
+
+            [profiles.prod.auth]
+            token = "secret"
+            metadata = { x-tenant = "ops", x-team = "core" }
+            "#;
+        let settings = load(
+            config,
+            &[
+                "--profile",
+                "prod",
+                "--address",
+                "http://127.0.0.1:9000",
+                "--connect-timeout",
+                "500ms",
+                "--poll-interval",
+                "5s",
+                "--metadata",
+                "x-tenant=dev",
+            ],
+        )
+        .expect("valid config");
+        assert_eq!(settings.address, "http://127.0.0.1:9000");
+        assert_eq!(settings.client.connect_timeout, Duration::from_millis(500));
+        assert_eq!(settings.poll_interval, Duration::from_secs(5));
+        assert_eq!(
+            settings.client.auth.metadata,
+            [
+                ("x-team".to_string(), "core".to_string()),
+                ("x-tenant".to_string(), "dev".to_string()),
+            ]
+        );
+
+        // The positional address overrides the profile too
+        let settings =
+            load(config, &["--profile", "prod", "http://127.0.0.1:9001"]).expect("valid config");
+        assert_eq!(settings.address, "http://127.0.0.1:9001");
+        assert_eq!(settings.poll_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn unknown_keys() {
+        assert_eq!(
+            load_err(
+                "[profiles.local]\nadress = \"http://127.0.0.1:50051\"\n",
+                &[]
+            ),
+            "invalid config config.toml: TOML parse error at line 2, column 1\n  \
+             |\n2 | adress = \"http://127.0.0.1:50051\"\n  | ^^^^^^\nunknown field \
+             `adress`, expected one of `address`, `connect_timeout`, `request_timeout`, \
+             `poll_interval`, `log_min_level`, `tail_lines`, `log_buffer_lines`, \
+             `alerts`, `tls`, `auth`, `confirm`, `log_file`"
+        );
+        assert_eq!(
+            load_err("profile = \"local\"\n", &[]),
+            "invalid config config.toml: TOML parse error at line 1, column 1\n  \
+             |\n1 | profile = \"local\"\n  | ^^^^^^^\nunknown field `profile`, expected \
+             one of `default_profile`, `audit_file`, `profiles`, `fleet`, `keys`"
+        );
+    }
+
+    #[test]
+    fn unknown_profile() {
+        let config = "[profiles.local]\n[profiles.prod]\n";
+        assert_eq!(
+            load_err(config, &["--profile", "staging"]),
+            "unknown profile 'staging' in config.toml (available: local, prod)"
+        );
+        assert_eq!(
+            load_err("", &["--profile", "staging"]),
+            "unknown profile 'staging': no profiles defined in config.toml"
+        );
+        assert_eq!(
+            load_err("default_profile = \"staging\"\n", &[]),
+            "unknown profile 'staging': no profiles defined in config.toml"
+        );
+    }
+
+    #[test]
+    fn bad_durations() {
+        assert_eq!(
+            load_err(
+                "[profiles.local]\npoll_interval = \"fast\"\n",
+                &["-p", "local"]
+            ),
+            "profile 'local' in config.toml: poll_interval: invalid duration 'fast'"
+        );
+        assert_eq!(
+            load_err(
+                "[profiles.local]\nconnect_timeout = \"0s\"\n",
+                &["-p", "local"]
+            ),
+            "profile 'local' in config.toml: connect_timeout: must be greater than zero"
+        );
+        assert_eq!(
+            load_err(
+                "[fleet]\nsettle_timeout = \"2y\"\n[profiles.local]\n",
+                &["--fleet"]
+            ),
+            "fleet.settle_timeout: unknown unit 'y' in duration '2y' (use ms, s, m or h)"
+        );
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512B"), Ok(512));
+        assert_eq!(parse_size("512KB"), Ok(512 * 1024));
+        assert_eq!(parse_size("10 mb"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("2GB"), Ok(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn bad_sizes() {
+        assert_eq!(parse_size("MB"), Err("invalid size 'MB'".to_string()));
+        assert_eq!(
+            parse_size("10TB"),
+            Err("unknown unit 'TB' in size '10TB' (use B, KB, MB or GB)".to_string())
+        );
+        assert_eq!(
+            parse_size("99999999999GB"),
+            Err("size '99999999999GB' is too large".to_string())
+        );
+        assert_eq!(
+            load_err(
+                "[profiles.local.log_file]\npath = \"daemon.jsonl\"\nmax_size = \"1.5MB\"\n",
+                &["-p", "local"]
+            ),
+            "profile 'local' in config.toml: log_file: max_size: unknown unit '.5MB' in \
+             size '1.5MB' (use B, KB, MB or GB)"
+        );
+    }
+}
@@ -1,7 +1,19 @@
-use std::time::Duration;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
 
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
 use tokio::sync::mpsc;
+use tonic::{Code, Status};
+
+use crate::app::ControlAction;
+use crate::bulk::TargetStatus;
+use crate::fleet::FleetPoll;
+use daemon_controller::grpc::{
+    daemon::{ControlResponse, LogEntry, MetricsResponse, StatusResponse},
+    LogStreamEvent,
+};
 
 /// Application events
 #[derive(Debug, Clone)]
@@ -15,14 +27,118 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize (handled automatically by ratatui)
     Resize(u16, u16),
+    /// The client worker connected to the daemon
+    Connected { reconnected: bool },
+    /// An explicit connection attempt failed
+    ConnectFailed {
+        /// Short description of the failure
+        summary: &'static str,
+        error: String,
+    },
+    /// The connection was lost or a reconnection attempt failed
+    Reconnecting {
+        attempt: u32,
+        next_retry: Instant,
+        error: String,
+    },
+    /// The client worker disconnected on request
+    Disconnected,
+    /// Result of fetching the daemon status
+    StatusUpdated(Result<StatusResponse, CallError>),
+    /// Result of fetching the daemon metrics
+    MetricsUpdated(Result<MetricsResponse, CallError>),
+    /// Result of a control command
+    ControlFinished(ControlAction, Result<ControlResponse, CallError>),
+    /// A control command could not be written to the audit file
+    AuditFailed(String),
+    /// Streamed daemon logs could not be written to the log file
+    LogFileFailed(String),
+    /// Log entry streamed from the daemon
+    DaemonLog(LogEntry),
+    /// The daemon log stream dropped and is being re-opened
+    LogStreamDropped(String),
+    /// Result of polling a member of the fleet
+    FleetPolled { index: usize, poll: FleetPoll },
+    /// Progress of a bulk action on the fleet member at `index`
+    BulkProgress { index: usize, status: TargetStatus },
+    /// A bulk action ran to completion or halted
+    BulkFinished,
+    /// Event posted by a task of the dashboard session with the given id
+    Session { session: u64, event: Box<Event> },
+}
+
+/// Why a daemon call failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// The daemon did not accept the credentials, or none were sent
+    Unauthenticated(String),
+    /// The credentials are valid but do not allow the call
+    PermissionDenied(String),
+    Other(String),
+}
+
+impl CallError {
+    /// Short label for authentication failures
+    pub fn auth_label(&self) -> Option<&'static str> {
+        match self {
+            Self::Unauthenticated(_) => Some("Unauthenticated"),
+            Self::PermissionDenied(_) => Some("Permission denied"),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// Suggested fix for authentication failures
+    pub fn auth_hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Unauthenticated(_) => Some("check the configured token"),
+            Self::PermissionDenied(_) => Some("the token is not allowed to do this"),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for CallError {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match error.downcast_ref::<Status>() {
+            Some(status) if status.code() == Code::Unauthenticated => {
+                Self::Unauthenticated(status.message().to_string())
+            }
+            Some(status) if status.code() == Code::PermissionDenied => {
+                Self::PermissionDenied(status.message().to_string())
+            }
+            _ => Self::Other(error.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthenticated(message) => write!(f, "authentication failed: {}", message),
+            Self::PermissionDenied(message) => write!(f, "permission denied: {}", message),
+            Self::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<LogStreamEvent> for Event {
+    fn from(event: LogStreamEvent) -> Self {
+        match event {
+            LogStreamEvent::Entry(entry) => Event::DaemonLog(entry),
+            LogStreamEvent::Closed => {
+                Event::LogStreamDropped("Log stream closed by daemon".to_string())
+            }
+            LogStreamEvent::Dropped(reason) => Event::LogStreamDropped(reason),
+        }
+    }
 }
 
 /// Event handler that polls for terminal events
 pub struct EventHandler {
     /// Event receiver
     rx: mpsc::UnboundedReceiver<Event>,
-    /// Event sender (kept for potential future use)
-    _tx: mpsc::UnboundedSender<Event>,
+    /// Event sender, cloned for background tasks
+    tx: mpsc::UnboundedSender<Event>,
 }
 
 impl EventHandler {
@@ -31,8 +147,9 @@ impl EventHandler {
         let (tx, rx) = mpsc::unbounded_channel();
         let event_tx = tx.clone();
 
-        // Spawn event polling task
-        tokio::spawn(async move {
+        // Poll on a dedicated thread: crossterm's poll blocks, and running it
+        // on the runtime would starve other tasks on single-core machines
+        std::thread::spawn(move || {
             let mut last_tick = std::time::Instant::now();
             loop {
                 // Calculate timeout for next tick
@@ -42,23 +159,16 @@ impl EventHandler {
 
                 // Poll for events
                 if event::poll(timeout).unwrap_or(false) {
-                    match event::read() {
-                        Ok(CrosstermEvent::Key(key)) => {
-                            if event_tx.send(Event::Key(key)).is_err() {
-                                break;
-                            }
-                        }
-                        Ok(CrosstermEvent::Mouse(mouse)) => {
-                            if event_tx.send(Event::Mouse(mouse)).is_err() {
-                                break;
-                            }
+                    let event = match event::read() {
+                        Ok(CrosstermEvent::Key(key)) => Some(Event::Key(key)),
+                        Ok(CrosstermEvent::Mouse(mouse)) => Some(Event::Mouse(mouse)),
+                        Ok(CrosstermEvent::Resize(w, h)) => Some(Event::Resize(w, h)),
+                        _ => None,
+                    };
+                    if let Some(event) = event {
+                        if event_tx.send(event).is_err() {
+                            break;
                         }
-                        Ok(CrosstermEvent::Resize(w, h)) => {
-                            if event_tx.send(Event::Resize(w, h)).is_err() {
-                                break;
-                            }
-                        }
-                        _ => {}
                     }
                 }
 
@@ -72,7 +182,12 @@ impl EventHandler {
             }
         });
 
-        Self { rx, _tx: tx }
+        Self { rx, tx }
+    }
+
+    /// Get a sender for posting events from background tasks
+    pub fn sender(&self) -> mpsc::UnboundedSender<Event> {
+        self.tx.clone()
     }
 
     /// Receive the next event
@@ -80,3 +195,97 @@ impl EventHandler {
         self.rx.recv().await
     }
 }
+
+/// Sender for the tasks of a dashboard session.
+///
+/// Events are forwarded tagged with `session`, so that those still queued
+/// when the dashboard moves on to another daemon can be told apart and
+/// dropped. Forwarding stops once every clone of the sender is dropped.
+pub fn session_sender(
+    session: u64,
+    events: mpsc::UnboundedSender<Event>,
+) -> mpsc::UnboundedSender<Event> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let event = Event::Session {
+                session,
+                event: Box::new(event),
+            };
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call_error(status: Status) -> CallError {
+        let error: Box<dyn std::error::Error + Send + Sync> = Box::new(status);
+        error.into()
+    }
+
+    #[test]
+    fn auth_failures() {
+        let error = call_error(Status::unauthenticated("Invalid bearer token"));
+        assert_eq!(
+            error,
+            CallError::Unauthenticated("Invalid bearer token".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "authentication failed: Invalid bearer token"
+        );
+
+        let error = call_error(Status::permission_denied("Token is read-only"));
+        assert_eq!(
+            error,
+            CallError::PermissionDenied("Token is read-only".to_string())
+        );
+        assert_eq!(error.to_string(), "permission denied: Token is read-only");
+    }
+
+    #[test]
+    fn other_failures() {
+        let error = call_error(Status::internal("Injected failure"));
+        assert_eq!(error.auth_label(), None);
+        assert!(matches!(error, CallError::Other(_)));
+
+        let error: CallError =
+            Box::<dyn std::error::Error + Send + Sync>::from("Not connected to daemon").into();
+        assert_eq!(
+            error,
+            CallError::Other("Not connected to daemon".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn session_events_are_tagged_in_order() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let session = session_sender(7, tx);
+        session
+            .send(Event::Connected { reconnected: false })
+            .unwrap();
+        session.send(Event::Disconnected).unwrap();
+        drop(session);
+
+        let mut tagged = Vec::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                Event::Session { session, event } => tagged.push((session, *event)),
+                other => panic!("untagged event {:?}", other),
+            }
+        }
+        assert!(matches!(
+            tagged.as_slice(),
+            [
+                (7, Event::Connected { reconnected: false }),
+                (7, Event::Disconnected)
+            ]
+        ));
+    }
+}
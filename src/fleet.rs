@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::bulk::{BulkMenu, BulkOperation};
+use crate::config::Settings;
+use crate::events::{CallError, Event};
+use crate::metrics::{MetricsSample, Rates};
+use daemon_controller::grpc::{
+    daemon::{MetricsResponse, StatusResponse},
+    ConnectError, DaemonClient,
+};
+
+/// Outcome of polling one fleet member
+#[derive(Debug, Clone)]
+pub enum FleetPoll {
+    Ok {
+        status: StatusResponse,
+        metrics: MetricsResponse,
+        /// Round trip time of the status request
+        latency: Duration,
+    },
+    Failed {
+        /// Short description of the failure
+        summary: &'static str,
+        error: String,
+    },
+}
+
+/// Connection health of a fleet member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Health {
+    /// Not polled yet
+    Pending,
+    Up {
+        latency: Duration,
+    },
+    Down {
+        summary: &'static str,
+        error: String,
+    },
+}
+
+/// Latest known state of one daemon in the fleet
+#[derive(Debug, Clone)]
+pub struct FleetMember {
+    /// Profile the daemon is configured under
+    pub name: String,
+    pub address: String,
+    pub health: Health,
+    pub status: Option<StatusResponse>,
+    pub metrics: Option<MetricsResponse>,
+    /// Rates since the previous successful poll
+    pub rates: Option<Rates>,
+    last_sample: Option<MetricsSample>,
+}
+
+impl FleetMember {
+    fn new(name: String, address: String) -> Self {
+        Self {
+            name,
+            address,
+            health: Health::Pending,
+            status: None,
+            metrics: None,
+            rates: None,
+            last_sample: None,
+        }
+    }
+
+    fn apply(&mut self, poll: FleetPoll) {
+        match poll {
+            FleetPoll::Ok {
+                status,
+                metrics,
+                latency,
+            } => {
+                let sample = MetricsSample {
+                    at: Instant::now(),
+                    metrics,
+                };
+                self.rates = self
+                    .last_sample
+                    .as_ref()
+                    .and_then(|prev| Rates::between(prev, &sample));
+                self.last_sample = Some(sample);
+                self.health = Health::Up { latency };
+                self.status = Some(status);
+                self.metrics = Some(metrics);
+            }
+            FleetPoll::Failed { summary, error } => {
+                // Stale values would look current, so drop them
+                self.health = Health::Down { summary, error };
+                self.status = None;
+                self.metrics = None;
+                self.rates = None;
+                self.last_sample = None;
+            }
+        }
+    }
+}
+
+/// Daemons shown in the fleet view and the selected row
+#[derive(Debug, Clone, Default)]
+pub struct Fleet {
+    pub members: Vec<FleetMember>,
+    pub selected: usize,
+    /// Members marked as targets of a bulk action
+    pub marked: BTreeSet<usize>,
+    /// Bulk action menu, while open
+    pub menu: Option<BulkMenu>,
+    /// Current or most recent bulk action
+    pub operation: Option<BulkOperation>,
+}
+
+impl Fleet {
+    /// Create a fleet of not yet polled daemons
+    pub fn new(members: &[Settings]) -> Self {
+        Self {
+            members: members
+                .iter()
+                .map(|settings| {
+                    FleetMember::new(
+                        settings.profile.clone().unwrap_or_default(),
+                        settings.address.clone(),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Record the result of polling the member at `index`
+    pub fn apply(&mut self, index: usize, poll: FleetPoll) {
+        if let Some(member) = self.members.get_mut(index) {
+            member.apply(poll);
+        }
+    }
+
+    /// Number of members whose last poll succeeded
+    pub fn up_count(&self) -> usize {
+        self.members
+            .iter()
+            .filter(|m| matches!(m.health, Health::Up { .. }))
+            .count()
+    }
+
+    /// Select the next row
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.members.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select the previous row
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Mark or unmark the selected row
+    pub fn toggle_mark(&mut self) {
+        if !self.marked.remove(&self.selected) && self.selected < self.members.len() {
+            self.marked.insert(self.selected);
+        }
+    }
+
+    /// Mark every row, or clear the marks if all are marked
+    pub fn toggle_mark_all(&mut self) {
+        if self.marked.len() == self.members.len() {
+            self.marked.clear();
+        } else {
+            self.marked = (0..self.members.len()).collect();
+        }
+    }
+
+    /// Whether a bulk action is still running
+    pub fn bulk_running(&self) -> bool {
+        self.operation.as_ref().is_some_and(|op| !op.finished)
+    }
+}
+
+/// Background tasks polling every fleet member concurrently.
+///
+/// Each member has its own task and client, so a slow or unreachable daemon
+/// does not hold up the others. The tasks are aborted when the poller is
+/// dropped.
+pub struct FleetPoller {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl FleetPoller {
+    /// Start polling each member every `interval`
+    pub fn spawn(
+        members: &[Settings],
+        interval: Duration,
+        events: mpsc::UnboundedSender<Event>,
+    ) -> Self {
+        let tasks = members
+            .iter()
+            .enumerate()
+            .map(|(index, settings)| {
+                let client =
+                    DaemonClient::with_options(settings.address.clone(), settings.client.clone());
+                tokio::spawn(poll_member(index, client, interval, events.clone()))
+            })
+            .collect();
+        Self { tasks }
+    }
+}
+
+impl Drop for FleetPoller {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Poll one daemon until the receiver goes away
+async fn poll_member(
+    index: usize,
+    mut client: DaemonClient,
+    interval: Duration,
+    events: mpsc::UnboundedSender<Event>,
+) {
+    loop {
+        let started = tokio::time::Instant::now();
+        let poll = poll_once(&mut client).await;
+        if events.send(Event::FleetPolled { index, poll }).is_err() {
+            return;
+        }
+        tokio::time::sleep_until(started + interval).await;
+    }
+}
+
+/// Connect if needed, then fetch status and metrics
+async fn poll_once(client: &mut DaemonClient) -> FleetPoll {
+    if !client.is_connected() {
+        if let Err(e) = client.connect().await {
+            return FleetPoll::Failed {
+                summary: e
+                    .downcast_ref::<ConnectError>()
+                    .map_or("Connection failed", ConnectError::summary),
+                error: e.to_string(),
+            };
+        }
+    }
+
+    let started = Instant::now();
+    let status = match client.get_status().await {
+        Ok(status) => status,
+        Err(e) => return call_failed(e),
+    };
+    let latency = started.elapsed();
+    match client.get_metrics().await {
+        Ok(metrics) => FleetPoll::Ok {
+            status,
+            metrics,
+            latency,
+        },
+        Err(e) => call_failed(e),
+    }
+}
+
+fn call_failed(error: Box<dyn std::error::Error + Send + Sync>) -> FleetPoll {
+    // A failed TLS handshake can surface on the first call
+    if let Some(e) = error.downcast_ref::<ConnectError>() {
+        return FleetPoll::Failed {
+            summary: e.summary(),
+            error: e.to_string(),
+        };
+    }
+    let error = CallError::from(error);
+    FleetPoll::Failed {
+        summary: error.auth_label().unwrap_or("Request failed"),
+        error: error.to_string(),
+    }
+}
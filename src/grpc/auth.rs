@@ -0,0 +1,327 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use tonic::{
+    metadata::{AsciiMetadataKey, AsciiMetadataValue},
+    service::Interceptor,
+    Request, Status,
+};
+
+/// Where the bearer token sent to the daemon comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    /// A fixed token
+    Value(String),
+    /// A file holding the token, re-read whenever it changes
+    File(PathBuf),
+}
+
+/// Credentials and extra metadata attached to every request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthOptions {
+    /// Token sent as `authorization: Bearer <token>`
+    pub token: Option<TokenSource>,
+    /// Additional ASCII metadata headers
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Interceptor adding the configured token and metadata to each request
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    token: Option<Arc<Token>>,
+    metadata: Arc<[(AsciiMetadataKey, AsciiMetadataValue)]>,
+}
+
+impl AuthInterceptor {
+    /// Validate the options and build an interceptor
+    pub fn new(options: &AuthOptions) -> Result<Self, String> {
+        let metadata = options
+            .metadata
+            .iter()
+            .map(|(key, value)| {
+                let key = AsciiMetadataKey::from_bytes(key.as_bytes())
+                    .map_err(|_| format!("invalid metadata key '{}'", key))?;
+                let value = AsciiMetadataValue::try_from(value.as_str())
+                    .map_err(|_| format!("invalid value for metadata key '{}'", key))?;
+                Ok((key, value))
+            })
+            .collect::<Result<_, String>>()?;
+
+        let token = match &options.token {
+            Some(TokenSource::Value(token)) => Some(Token::Value(bearer(token)?)),
+            Some(TokenSource::File(path)) => Some(Token::File {
+                path: path.clone(),
+                cached: Mutex::new(None),
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            token: token.map(Arc::new),
+            metadata,
+        })
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let metadata = request.metadata_mut();
+        for (key, value) in self.metadata.iter() {
+            metadata.insert(key.clone(), value.clone());
+        }
+        if let Some(token) = &self.token {
+            let header = token.header().map_err(Status::unauthenticated)?;
+            metadata.insert("authorization", header);
+        }
+        Ok(request)
+    }
+}
+
+enum Token {
+    Value(AsciiMetadataValue),
+    File {
+        path: PathBuf,
+        /// Header built from the file, keyed by its modification time and size
+        cached: Mutex<Option<((SystemTime, u64), AsciiMetadataValue)>>,
+    },
+}
+
+impl Token {
+    /// Current `authorization` header value
+    fn header(&self) -> Result<AsciiMetadataValue, String> {
+        let (path, cached) = match self {
+            Self::Value(value) => return Ok(value.clone()),
+            Self::File { path, cached } => (path, cached),
+        };
+        let failed = |e: String| format!("token file {}: {}", path.display(), e);
+
+        let meta = fs::metadata(path).map_err(|e| failed(e.to_string()))?;
+        let version = (
+            meta.modified().map_err(|e| failed(e.to_string()))?,
+            meta.len(),
+        );
+        let mut cached = cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_version, value)) = &*cached {
+            if *cached_version == version {
+                return Ok(value.clone());
+            }
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| failed(e.to_string()))?;
+        let value = bearer(contents.trim()).map_err(failed)?;
+        *cached = Some((version, value.clone()));
+        Ok(value)
+    }
+}
+
+fn bearer(token: &str) -> Result<AsciiMetadataValue, String> {
+    if token.is_empty() {
+        return Err("token is empty".to_string());
+    }
+    format!("Bearer {}", token)
+        .parse()
+        .map_err(|_| "token contains invalid characters".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        net::{SocketAddr, TcpListener},
+        time::Duration,
+    };
+
+    use tokio::task::JoinHandle;
+    use tonic::Code;
+
+    use super::*;
+    use crate::grpc::{daemon::ControlCommand, ClientOptions, DaemonClient};
+    use crate::mock::{self, MockConfig, MockDaemon};
+
+    /// `authorization` header the interceptor adds to a request, or the code
+    /// and message of the status it fails with
+    fn authorization(interceptor: &mut AuthInterceptor) -> Result<String, (Code, String)> {
+        let request = interceptor
+            .call(Request::new(()))
+            .map_err(|status| (status.code(), status.message().to_string()))?;
+        let header = request.metadata().get("authorization");
+        Ok(header.map_or_else(String::new, |value| value.to_str().unwrap().to_string()))
+    }
+
+    fn token_file(path: PathBuf) -> AuthInterceptor {
+        AuthInterceptor::new(&AuthOptions {
+            token: Some(TokenSource::File(path)),
+            ..AuthOptions::default()
+        })
+        .expect("valid options")
+    }
+
+    /// Serve a mock daemon requiring `secret`, or `viewer` for reads,
+    /// returning its address
+    async fn daemon() -> (String, JoinHandle<()>) {
+        let addr: SocketAddr = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("pick a free port");
+        let daemon = MockDaemon::new(MockConfig {
+            auth_token: Some("secret".to_string()),
+            read_only_token: Some("viewer".to_string()),
+            ..MockConfig::default()
+        });
+        let server = tokio::spawn(async move {
+            mock::serve(addr, daemon, Duration::ZERO, None)
+                .await
+                .expect("mock daemon failed");
+        });
+        (format!("http://{}", addr), server)
+    }
+
+    async fn connect(address: &str, token: &str) -> DaemonClient {
+        let options = ClientOptions {
+            auth: AuthOptions {
+                token: Some(TokenSource::Value(token.to_string())),
+                ..AuthOptions::default()
+            },
+            ..ClientOptions::default()
+        };
+        let mut client = DaemonClient::with_options(address.to_string(), options);
+        for _ in 0..100 {
+            if client.connect().await.is_ok() {
+                return client;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("mock daemon did not start");
+    }
+
+    fn code(error: Box<dyn std::error::Error + Send + Sync>) -> (Code, String) {
+        let status = error.downcast_ref::<Status>().expect("gRPC status");
+        (status.code(), status.message().to_string())
+    }
+
+    #[test]
+    fn token_and_metadata() {
+        let mut interceptor = AuthInterceptor::new(&AuthOptions {
+            token: Some(TokenSource::Value("secret".to_string())),
+            metadata: vec![
+                ("x-tenant".to_string(), "ops".to_string()),
+                ("x-team".to_string(), "core".to_string()),
+            ],
+        })
+        .expect("valid options");
+        let request = interceptor.call(Request::new(())).expect("intercept");
+        let metadata = request.metadata();
+        assert_eq!(metadata.get("authorization").unwrap(), "Bearer secret");
+        assert_eq!(metadata.get("x-tenant").unwrap(), "ops");
+        assert_eq!(metadata.get("x-team").unwrap(), "core");
+
+        let mut interceptor = AuthInterceptor::new(&AuthOptions::default()).expect("valid");
+        assert_eq!(authorization(&mut interceptor).unwrap(), "");
+    }
+
+    #[test]
+    fn bad_options() {
+        let options = |token: &str, key: &str| AuthOptions {
+            token: Some(TokenSource::Value(token.to_string())),
+            metadata: vec![(key.to_string(), "value".to_string())],
+        };
+        let error = |options| AuthInterceptor::new(&options).err();
+        assert_eq!(
+            error(options("secret", "x tenant")),
+            Some("invalid metadata key 'x tenant'".to_string())
+        );
+        assert_eq!(
+            error(options("", "x-tenant")),
+            Some("token is empty".to_string())
+        );
+        assert_eq!(
+            error(options("sec\nret", "x-tenant")),
+            Some("token contains invalid characters".to_string())
+        );
+    }
+
+    #[test]
+    fn token_file_is_reread_when_it_changes() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("token");
+        fs::write(&path, "one\n").expect("write token");
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).unwrap();
+        let mut interceptor = token_file(path.clone());
+        assert_eq!(authorization(&mut interceptor).unwrap(), "Bearer one");
+
+        // Same size and modification time: the cached header is kept
+        fs::write(&path, "two\n").expect("write token");
+        let set_modified = |time| {
+            File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(time))
+                .expect("set modification time")
+        };
+        set_modified(modified);
+        assert_eq!(authorization(&mut interceptor).unwrap(), "Bearer one");
+
+        set_modified(modified + Duration::from_secs(1));
+        assert_eq!(authorization(&mut interceptor).unwrap(), "Bearer two");
+
+        // A new length is noticed even within the same modification time
+        fs::write(&path, "three\n").expect("write token");
+        set_modified(modified + Duration::from_secs(1));
+        assert_eq!(authorization(&mut interceptor).unwrap(), "Bearer three");
+    }
+
+    #[test]
+    fn unreadable_token_file() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("token");
+        fs::write(&path, "\n").expect("write token");
+        let mut interceptor = token_file(path.clone());
+        assert_eq!(
+            authorization(&mut interceptor),
+            Err((
+                Code::Unauthenticated,
+                format!("token file {}: token is empty", path.display())
+            ))
+        );
+
+        fs::remove_file(&path).expect("remove token");
+        let (code, message) = authorization(&mut interceptor).expect_err("missing file");
+        assert_eq!(code, Code::Unauthenticated);
+        assert!(message.starts_with(&format!("token file {}: ", path.display())));
+    }
+
+    #[tokio::test]
+    async fn daemon_checks_the_token() {
+        let (address, server) = daemon().await;
+
+        let mut client = connect(&address, "secret").await;
+        client.get_status().await.expect("status");
+        let response = client
+            .control(ControlCommand::Reload)
+            .await
+            .expect("control");
+        assert!(response.success);
+
+        let mut client = connect(&address, "wrong").await;
+        assert_eq!(
+            code(client.get_status().await.expect_err("bad token")),
+            (Code::Unauthenticated, "Invalid bearer token".to_string())
+        );
+
+        let mut client = connect(&address, "viewer").await;
+        client.get_status().await.expect("status");
+        assert_eq!(
+            code(
+                client
+                    .control(ControlCommand::Reload)
+                    .await
+                    .expect_err("read-only token")
+            ),
+            (Code::PermissionDenied, "Token is read-only".to_string())
+        );
+        server.abort();
+    }
+}
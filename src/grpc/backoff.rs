@@ -0,0 +1,120 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Capped exponential backoff with jitter
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Create a backoff starting at `base` and never exceeding `max`
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Number of delays handed out since the last reset
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Reset after a successful attempt
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Get the delay before the next attempt and advance the attempt count.
+    ///
+    /// Uses "equal jitter": half of the capped exponential delay is fixed
+    /// and the other half is random, so retries from many clients spread out
+    /// without ever retrying immediately.
+    pub fn next_delay(&mut self) -> Duration {
+        let exp = self.base.saturating_mul(1 << self.attempt.min(16));
+        let capped = exp.min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+
+        let half = capped / 2;
+        half + half.mul_f64(random_unit())
+    }
+}
+
+/// Random value in `[0, 1)` without pulling in an RNG dependency
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_double_within_jitter_bounds() {
+        let base = Duration::from_millis(100);
+        let mut backoff = Backoff::new(base, Duration::from_secs(60));
+        for attempt in 0..5 {
+            let exp = base * (1 << attempt);
+            let delay = backoff.next_delay();
+            assert!(
+                delay >= exp / 2 && delay <= exp,
+                "attempt {}: {:?} outside {:?}..={:?}",
+                attempt,
+                delay,
+                exp / 2,
+                exp
+            );
+        }
+        assert_eq!(backoff.attempt(), 5);
+    }
+
+    #[test]
+    fn delays_stop_growing_at_the_cap() {
+        let max = Duration::from_secs(5);
+        let mut backoff = Backoff::new(Duration::from_millis(100), max);
+        for _ in 0..100 {
+            let delay = backoff.next_delay();
+            if backoff.attempt() > 6 {
+                assert!(delay >= max / 2 && delay <= max, "{:?}", delay);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_attempt_counts_do_not_overflow() {
+        let max = Duration::from_secs(30);
+        let mut backoff = Backoff::new(Duration::from_secs(u64::MAX / 2), max);
+        backoff.attempt = u32::MAX;
+        let delay = backoff.next_delay();
+        assert!(delay >= max / 2 && delay <= max);
+        assert_eq!(backoff.attempt(), u32::MAX);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let base = Duration::from_millis(100);
+        let mut backoff = Backoff::new(base, Duration::from_secs(60));
+        for _ in 0..10 {
+            backoff.next_delay();
+        }
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert!(backoff.next_delay() <= base);
+    }
+
+    #[test]
+    fn random_unit_is_in_range() {
+        for _ in 0..1000 {
+            let value = random_unit();
+            assert!((0.0..1.0).contains(&value), "{}", value);
+        }
+    }
+}
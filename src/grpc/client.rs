@@ -1,47 +1,319 @@
-use std::time::Duration;
+use std::{
+    error::Error as _,
+    fmt,
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use tonic::transport::Channel;
+use tokio::sync::mpsc;
+use tonic::{
+    service::interceptor::InterceptedService,
+    transport::{Channel, Endpoint, Uri},
+    Code, Status,
+};
 
+use super::auth::{AuthInterceptor, AuthOptions};
+use super::backoff::Backoff;
 use super::daemon::{
     daemon_service_client::DaemonServiceClient, ControlCommand, ControlRequest, ControlResponse,
-    MetricsRequest, MetricsResponse, StatusRequest, StatusResponse,
+    LogLevel, LogsRequest, MetricsRequest, MetricsResponse, StatusRequest, StatusResponse,
 };
+use super::logs::{LogStreamEvent, LogSubscription};
+use super::tls::{tls_failure, TlsOptions};
+use super::unix::socket_path;
+#[cfg(unix)]
+use super::unix::{UnixConnector, SOCKET_ORIGIN};
+
+/// Initial delay before reconnecting after a lost connection
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on the delay between reconnection attempts
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Connection settings for a [`DaemonClient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// Time allowed to establish the connection
+    pub connect_timeout: Duration,
+    /// Time allowed for each unary call
+    pub request_timeout: Duration,
+    /// TLS settings, used for `https://` addresses
+    pub tls: TlsOptions,
+    /// Token and metadata sent with every request
+    pub auth: AuthOptions,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(10),
+            tls: TlsOptions::default(),
+            auth: AuthOptions::default(),
+        }
+    }
+}
+
+/// Generated client with the authentication interceptor applied
+pub(crate) type ServiceClient = DaemonServiceClient<InterceptedService<Channel, AuthInterceptor>>;
+
+/// Why a connection attempt failed
+#[derive(Debug)]
+pub enum ConnectError {
+    /// The address or TLS settings are unusable
+    Config(String),
+    /// The daemon could not be reached
+    Unreachable(String),
+    /// The daemon was reached but the TLS handshake failed
+    Handshake(String),
+}
+
+impl ConnectError {
+    /// Short description suitable for a status line
+    pub fn summary(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "Invalid connection settings",
+            Self::Unreachable(_) => "Daemon unreachable",
+            Self::Handshake(_) => "TLS handshake failed",
+        }
+    }
+
+    fn from_transport(error: tonic::transport::Error, secure: bool) -> Self {
+        if let Some(reason) = secure.then(|| tls_failure(&error)).flatten() {
+            return Self::Handshake(reason);
+        }
+        // The outer errors only say "transport error"; the root cause is useful
+        let mut cause: &dyn std::error::Error = &error;
+        while let Some(source) = cause.source() {
+            cause = source;
+        }
+        Self::Unreachable(cause.to_string())
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(reason) => write!(f, "invalid connection settings: {}", reason),
+            Self::Unreachable(reason) => write!(f, "daemon unreachable: {}", reason),
+            Self::Handshake(reason) => write!(f, "TLS handshake failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConnectError {}
+
+/// Pending reconnection after the connection was lost
+#[derive(Debug, Clone, Copy)]
+pub struct Reconnect {
+    /// Number of the next attempt, starting at 1
+    pub attempt: u32,
+    /// When the next attempt is due
+    pub next_retry: Instant,
+}
 
 /// Wrapper around the gRPC client with connection management
 pub struct DaemonClient {
-    client: Option<DaemonServiceClient<Channel>>,
+    client: Option<ServiceClient>,
+    logs: Option<LogSubscription>,
     address: String,
+    options: ClientOptions,
+    backoff: Backoff,
+    reconnect: Option<Reconnect>,
 }
 
 impl DaemonClient {
-    /// Create a new daemon client
+    /// Create a new daemon client with default options
     pub fn new(address: String) -> Self {
+        Self::with_options(address, ClientOptions::default())
+    }
+
+    /// Create a new daemon client with the given connection options
+    pub fn with_options(address: String, options: ClientOptions) -> Self {
         Self {
             client: None,
+            logs: None,
             address,
+            options,
+            backoff: Backoff::new(RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY),
+            reconnect: None,
         }
     }
 
+    /// Whether the connection is encrypted
+    pub fn uses_tls(&self) -> bool {
+        self.address.starts_with("https://")
+    }
+
     /// Check if connected
     pub fn is_connected(&self) -> bool {
         self.client.is_some()
     }
 
-    /// Connect to the daemon
+    /// Get the pending reconnection, if the connection was lost
+    pub fn reconnect_state(&self) -> Option<Reconnect> {
+        self.reconnect
+    }
+
+    /// Attempt to reconnect if a retry is due.
+    ///
+    /// Returns `None` when no attempt was made. On failure the next attempt
+    /// is scheduled with exponential backoff.
+    pub async fn try_reconnect(
+        &mut self,
+    ) -> Option<Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        let reconnect = self.reconnect?;
+        if Instant::now() < reconnect.next_retry {
+            return None;
+        }
+
+        let result = self.connect().await;
+        if result.is_err() {
+            self.schedule_reconnect();
+        }
+        Some(result)
+    }
+
+    /// Connect to the daemon.
+    ///
+    /// Failures are reported as a [`ConnectError`].
     pub async fn connect(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let channel = Channel::from_shared(self.address.clone())?
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(10))
-            .connect()
-            .await?;
+        let interceptor = AuthInterceptor::new(&self.options.auth).map_err(ConnectError::Config)?;
+        let channel = self.open_channel().await?;
 
-        self.client = Some(DaemonServiceClient::new(channel));
+        self.client = Some(DaemonServiceClient::with_interceptor(channel, interceptor));
+        self.reconnect = None;
+        self.backoff.reset();
         Ok(())
     }
 
-    /// Disconnect from the daemon
+    /// Open a channel to the daemon, using TLS for `https://` addresses
+    async fn open_channel(&self) -> Result<Channel, ConnectError> {
+        if let Some(path) = socket_path(&self.address) {
+            if self.options.tls.is_customized() {
+                return Err(ConnectError::Config(
+                    "TLS settings are not supported for unix: addresses".to_string(),
+                ));
+            }
+            return self.open_socket_channel(path).await;
+        }
+
+        let uri: Uri = self.address.parse().map_err(|e| {
+            ConnectError::Config(format!("invalid address '{}': {}", self.address, e))
+        })?;
+        let tls = &self.options.tls;
+        let secure = self.uses_tls();
+        if !secure && tls.is_customized() {
+            return Err(ConnectError::Config(
+                "TLS settings require an https:// address".to_string(),
+            ));
+        }
+
+        let result = if !secure {
+            self.endpoint(uri).connect().await
+        } else if tls.insecure_skip_verify {
+            let connector = tls.insecure_connector(&uri).map_err(ConnectError::Config)?;
+            // The connector does the handshake, so tonic must not attempt its
+            // own; requests still carry the https origin
+            let mut parts = uri.clone().into_parts();
+            parts.scheme = Some("http".parse().expect("valid scheme"));
+            let plain = Uri::from_parts(parts).map_err(|e| ConnectError::Config(e.to_string()))?;
+            self.endpoint(plain)
+                .origin(uri)
+                .connect_with_connector(connector)
+                .await
+        } else {
+            let config = tls.client_config().map_err(ConnectError::Config)?;
+            self.endpoint(uri)
+                .tls_config(config)
+                .map_err(|e| ConnectError::Config(format!("invalid TLS settings: {}", e)))?
+                .connect()
+                .await
+        };
+        result.map_err(|e| ConnectError::from_transport(e, secure))
+    }
+
+    /// Open a plaintext channel over the Unix domain socket at `path`
+    #[cfg(unix)]
+    async fn open_socket_channel(&self, path: &Path) -> Result<Channel, ConnectError> {
+        self.endpoint(Uri::from_static(SOCKET_ORIGIN))
+            .connect_with_connector(UnixConnector::new(path))
+            .await
+            .map_err(|e| ConnectError::from_transport(e, false))
+    }
+
+    #[cfg(not(unix))]
+    async fn open_socket_channel(&self, _path: &Path) -> Result<Channel, ConnectError> {
+        Err(ConnectError::Config(
+            "unix: addresses are not supported on this platform".to_string(),
+        ))
+    }
+
+    fn endpoint(&self, uri: Uri) -> Endpoint {
+        Endpoint::from(uri)
+            .connect_timeout(self.options.connect_timeout)
+            .timeout(self.options.request_timeout)
+    }
+
+    /// Disconnect from the daemon, cancelling any pending reconnection
     pub fn disconnect(&mut self) {
+        self.logs = None;
+        self.client = None;
+        self.reconnect = None;
+        self.backoff.reset();
+    }
+
+    /// Drop the connection after a transport failure and start reconnecting
+    fn connection_lost(&mut self) {
+        self.logs = None;
         self.client = None;
+        self.schedule_reconnect();
+    }
+
+    /// Schedule the next reconnection attempt
+    fn schedule_reconnect(&mut self) {
+        let delay = self.backoff.next_delay();
+        self.reconnect = Some(Reconnect {
+            attempt: self.backoff.attempt(),
+            next_retry: Instant::now() + delay,
+        });
+    }
+
+    /// Inspect a failed call, dropping the connection on transport failure
+    fn check(&mut self, status: Status) -> Box<dyn std::error::Error + Send + Sync> {
+        // With TLS 1.3 a rejected client certificate only surfaces on the
+        // first call, after the handshake appeared to succeed
+        let handshake = self.uses_tls().then(|| tls_failure(&status)).flatten();
+        if handshake.is_some() || is_transport_failure(&status) {
+            self.connection_lost();
+        }
+        match handshake {
+            Some(reason) => ConnectError::Handshake(reason).into(),
+            None => status.into(),
+        }
+    }
+
+    /// Start streaming daemon logs into the given channel.
+    ///
+    /// Replaces any existing subscription. The stream is re-opened
+    /// automatically if it drops, and stopped on disconnect.
+    pub fn subscribe_logs<T>(
+        &mut self,
+        min_level: LogLevel,
+        tail_lines: u32,
+        tx: mpsc::UnboundedSender<T>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: From<LogStreamEvent> + Send + 'static,
+    {
+        let client = self.client.clone().ok_or("Not connected to daemon")?;
+
+        let request = LogsRequest {
+            min_level: min_level.into(),
+            tail_lines,
+        };
+        self.logs = Some(LogSubscription::spawn(client, request, tx));
+        Ok(())
     }
 
     /// Get daemon status
@@ -50,8 +322,10 @@ impl DaemonClient {
     ) -> Result<StatusResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.client.as_mut().ok_or("Not connected to daemon")?;
 
-        let response = client.get_status(StatusRequest {}).await?;
-        Ok(response.into_inner())
+        match client.get_status(StatusRequest {}).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) => Err(self.check(status)),
+        }
     }
 
     /// Get daemon metrics
@@ -60,8 +334,10 @@ impl DaemonClient {
     ) -> Result<MetricsResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.client.as_mut().ok_or("Not connected to daemon")?;
 
-        let response = client.get_metrics(MetricsRequest {}).await?;
-        Ok(response.into_inner())
+        match client.get_metrics(MetricsRequest {}).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) => Err(self.check(status)),
+        }
     }
 
     /// Send a control command
@@ -71,11 +347,22 @@ impl DaemonClient {
     ) -> Result<ControlResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.client.as_mut().ok_or("Not connected to daemon")?;
 
-        let response = client
+        let result = client
             .control(ControlRequest {
                 command: command.into(),
             })
-            .await?;
-        Ok(response.into_inner())
+            .await;
+        match result {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) => Err(self.check(status)),
+        }
     }
 }
+
+/// Whether a failed call indicates the connection itself is broken
+fn is_transport_failure(status: &Status) -> bool {
+    status.code() == Code::Unavailable
+        || status
+            .source()
+            .is_some_and(|source| source.is::<tonic::transport::Error>())
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use super::client::ServiceClient;
+use super::daemon::{LogEntry, LogsRequest};
+
+/// Delay before re-opening a log stream that was dropped
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// Notifications emitted by a log subscription
+#[derive(Debug, Clone)]
+pub enum LogStreamEvent {
+    /// A log entry received from the daemon
+    Entry(LogEntry),
+    /// The daemon ended the stream; it will be re-opened
+    Closed,
+    /// The stream failed and will be re-opened
+    Dropped(String),
+}
+
+/// Background task forwarding daemon log entries to a channel.
+///
+/// The task is aborted when the subscription is dropped.
+pub struct LogSubscription {
+    handle: JoinHandle<()>,
+}
+
+impl LogSubscription {
+    /// Spawn a task that streams logs from the daemon until dropped
+    pub fn spawn<T>(
+        mut client: ServiceClient,
+        mut request: LogsRequest,
+        tx: mpsc::UnboundedSender<T>,
+    ) -> Self
+    where
+        T: From<LogStreamEvent> + Send + 'static,
+    {
+        let handle = tokio::spawn(async move {
+            loop {
+                let end = match client.stream_logs(request).await {
+                    Ok(response) => {
+                        let mut stream = response.into_inner();
+                        loop {
+                            match stream.message().await {
+                                Ok(Some(entry)) => {
+                                    if tx.send(LogStreamEvent::Entry(entry).into()).is_err() {
+                                        return;
+                                    }
+                                }
+                                Ok(None) => break LogStreamEvent::Closed,
+                                Err(status) => {
+                                    break LogStreamEvent::Dropped(format!(
+                                        "Log stream error: {}",
+                                        status
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                    Err(status) => {
+                        LogStreamEvent::Dropped(format!("Failed to open log stream: {}", status))
+                    }
+                };
+
+                if tx.send(end.into()).is_err() {
+                    return;
+                }
+
+                // Only replay the tail on the first subscription
+                request.tail_lines = 0;
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+
+        Self { handle }
+    }
+}
+
+impl Drop for LogSubscription {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
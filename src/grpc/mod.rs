@@ -1,8 +1,53 @@
+pub mod auth;
+pub mod backoff;
 pub mod client;
+pub mod logs;
+pub mod tls;
+pub mod unix;
 
 // Include the generated protobuf code
 pub mod daemon {
     tonic::include_proto!("daemon");
 }
 
-pub use client::DaemonClient;
+pub use auth::{AuthOptions, TokenSource};
+pub use client::{ClientOptions, ConnectError, DaemonClient};
+pub use logs::LogStreamEvent;
+pub use tls::TlsOptions;
+pub use unix::socket_path;
+
+impl daemon::DaemonState {
+    /// Human-readable name of the state
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::Starting => "Starting",
+            Self::Running => "Running",
+            Self::Stopping => "Stopping",
+            Self::Stopped => "Stopped",
+            Self::Error => "Error",
+        }
+    }
+}
+
+impl daemon::LogLevel {
+    /// Short uppercase label used in log output
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+
+    /// Level with the given label, ignoring case
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::from_str_name(&format!("LOG_LEVEL_{}", label.to_ascii_uppercase()))
+    }
+
+    /// Next more severe level, if any
+    pub fn next(self) -> Option<Self> {
+        Self::try_from(self as i32 + 1).ok()
+    }
+}
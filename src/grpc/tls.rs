@@ -0,0 +1,456 @@
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{
+        self,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{self, CryptoProvider},
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        DigitallySignedStruct, SignatureScheme,
+    },
+    TlsConnector,
+};
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, Uri};
+use tower_service::Service;
+
+/// ALPN protocol id for HTTP/2, which gRPC requires
+const ALPN_H2: &[u8] = b"h2";
+
+/// TLS settings for the daemon connection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// PEM bundle of CAs trusted instead of the system roots
+    pub ca_cert: Option<PathBuf>,
+    /// PEM certificate presented to the daemon for mutual TLS
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert`
+    pub client_key: Option<PathBuf>,
+    /// Name checked against the server certificate instead of the address host
+    pub server_name: Option<String>,
+    /// Accept any server certificate. Only meant for development daemons.
+    pub insecure_skip_verify: bool,
+}
+
+impl TlsOptions {
+    /// Whether any setting differs from the defaults
+    pub fn is_customized(&self) -> bool {
+        self != &Self::default()
+    }
+
+    /// Build the configuration for tonic's verifying TLS connector
+    pub(crate) fn client_config(&self) -> Result<ClientTlsConfig, String> {
+        let mut config = ClientTlsConfig::new();
+        config = match &self.ca_cert {
+            Some(path) => config.ca_certificate(Certificate::from_pem(read(path, "CA bundle")?)),
+            None => config.with_native_roots(),
+        };
+        if let Some((cert, key)) = self.identity_paths()? {
+            config = config.identity(Identity::from_pem(
+                read(cert, "client certificate")?,
+                read(key, "client key")?,
+            ));
+        }
+        if let Some(name) = &self.server_name {
+            config = config.domain_name(name.clone());
+        }
+        Ok(config)
+    }
+
+    /// Build a connector that performs the handshake without verifying the
+    /// server certificate
+    pub(crate) fn insecure_connector(&self, uri: &Uri) -> Result<InsecureConnector, String> {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification(provider)));
+
+        let mut config = match self.identity_paths()? {
+            Some((cert, key)) => {
+                let certs =
+                    rustls_pemfile::certs(&mut read(cert, "client certificate")?.as_slice())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| {
+                            format!("invalid client certificate {}: {}", cert.display(), e)
+                        })?;
+                let key = rustls_pemfile::private_key(&mut read(key, "client key")?.as_slice())
+                    .map_err(|e| format!("invalid client key {}: {}", key.display(), e))?
+                    .ok_or_else(|| format!("no private key found in {}", key.display()))?;
+                builder
+                    .with_client_auth_cert(certs, key)
+                    .map_err(|e| format!("invalid client certificate: {}", e))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = vec![ALPN_H2.to_vec()];
+
+        let host = match &self.server_name {
+            Some(name) => name.as_str(),
+            None => uri.host().ok_or("address has no host")?,
+        };
+        // IPv6 hosts keep their brackets in the URI
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| format!("invalid server name '{}': {}", host, e))?;
+
+        Ok(InsecureConnector {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        })
+    }
+
+    /// Client certificate and key paths, which must be given together
+    fn identity_paths(&self) -> Result<Option<(&Path, &Path)>, String> {
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Ok(Some((cert, key))),
+            (None, None) => Ok(None),
+            _ => Err("client certificate and key must be given together".to_string()),
+        }
+    }
+}
+
+fn read(path: &Path, what: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("failed to read {} {}: {}", what, path.display(), e))
+}
+
+/// Connector that wraps TCP connections in TLS without checking the server
+/// certificate.
+///
+/// tonic's own connector always verifies, so this is used in its place when
+/// verification is disabled.
+#[derive(Clone)]
+pub(crate) struct InsecureConnector {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl Service<Uri> for InsecureConnector {
+    type Response = TokioIo<TlsStream<TcpStream>>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connector = self.connector.clone();
+        let server_name = self.server_name.clone();
+        Box::pin(async move {
+            let host = uri.host().ok_or("address has no host")?;
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            let port = uri.port_u16().unwrap_or(443);
+
+            let tcp = TcpStream::connect((host, port)).await?;
+            tcp.set_nodelay(true)?;
+            let tls = connector.connect(server_name, tcp).await?;
+            Ok(TokioIo::new(tls))
+        })
+    }
+}
+
+/// Certificate verifier that accepts any server certificate but still checks
+/// handshake signatures, so the peer must hold the certificate's key
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Find a TLS failure in the error chain of a TLS connection, returning its
+/// description.
+///
+/// rustls reports failures as `InvalidData` I/O errors, and some layers keep
+/// only the message of the rustls error, so both forms are recognized.
+pub(crate) fn tls_failure(error: &(dyn std::error::Error + 'static)) -> Option<String> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(e) = error.downcast_ref::<rustls::Error>() {
+            return Some(e.to_string());
+        }
+        if let Some(e) = error.downcast_ref::<std::io::Error>() {
+            if e.kind() == std::io::ErrorKind::InvalidData {
+                return Some(match e.get_ref() {
+                    Some(inner) => inner.to_string(),
+                    None => e.to_string(),
+                });
+            }
+        }
+        current = error.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, fmt, io, net::TcpListener, time::Duration};
+
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use tempfile::TempDir;
+    use tokio::task::JoinHandle;
+    use tonic::transport::ServerTlsConfig;
+
+    use super::*;
+    use crate::{
+        grpc::{ClientOptions, ConnectError, DaemonClient},
+        mock::{self, MockConfig, MockDaemon},
+    };
+
+    /// Error wrapping another, like the transport errors around a handshake
+    #[derive(Debug)]
+    struct Wrapper(Box<dyn Error + Send + Sync>);
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("transport error")
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(self.0.as_ref())
+        }
+    }
+
+    fn unknown_issuer() -> rustls::Error {
+        rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer)
+    }
+
+    #[test]
+    fn rustls_errors() {
+        let expected = Some(unknown_issuer().to_string());
+        assert_eq!(tls_failure(&unknown_issuer()), expected);
+        assert_eq!(tls_failure(&Wrapper(Box::new(unknown_issuer()))), expected);
+    }
+
+    #[test]
+    fn invalid_data_io_errors() {
+        let wrapped = io::Error::new(io::ErrorKind::InvalidData, unknown_issuer());
+        assert_eq!(
+            tls_failure(&Wrapper(Box::new(wrapped))),
+            Some(unknown_issuer().to_string())
+        );
+
+        // Only the message of the rustls error survived
+        let message = io::Error::new(io::ErrorKind::InvalidData, "received corrupt message");
+        assert_eq!(
+            tls_failure(&Wrapper(Box::new(message))),
+            Some("received corrupt message".to_string())
+        );
+    }
+
+    #[test]
+    fn other_errors() {
+        let refused = io::Error::from(io::ErrorKind::ConnectionRefused);
+        assert_eq!(tls_failure(&Wrapper(Box::new(refused))), None);
+        assert_eq!(tls_failure(&Wrapper("connection reset".into())), None);
+    }
+
+    /// Generated CA and the server and client certificates it signed, as PEM
+    /// files in a temporary directory
+    struct Pki {
+        dir: TempDir,
+        ca: String,
+        server: (String, String),
+        client: (String, String),
+    }
+
+    impl Pki {
+        fn generate() -> Self {
+            let ca_key = KeyPair::generate().expect("CA key");
+            let mut params = CertificateParams::new(Vec::<String>::new()).expect("CA params");
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = params.self_signed(&ca_key).expect("CA certificate");
+
+            let issue = |name: &str| {
+                let key = KeyPair::generate().expect("key");
+                let cert = CertificateParams::new(vec![name.to_string()])
+                    .and_then(|params| params.signed_by(&key, &ca, &ca_key))
+                    .expect("certificate");
+                (cert.pem(), key.serialize_pem())
+            };
+            Self {
+                dir: tempfile::tempdir().expect("temp dir"),
+                ca: ca.pem(),
+                server: issue("daemon.test"),
+                client: issue("controller.test"),
+            }
+        }
+
+        fn write(&self, name: &str, pem: &str) -> PathBuf {
+            let path = self.dir.path().join(name);
+            fs::write(&path, pem).expect("write PEM file");
+            path
+        }
+
+        /// Options trusting the generated CA, checking the server's name
+        fn trusted(&self) -> TlsOptions {
+            TlsOptions {
+                ca_cert: Some(self.write("ca.pem", &self.ca)),
+                server_name: Some("daemon.test".to_string()),
+                ..TlsOptions::default()
+            }
+        }
+
+        fn with_client_cert(&self, options: TlsOptions) -> TlsOptions {
+            TlsOptions {
+                client_cert: Some(self.write("client.pem", &self.client.0)),
+                client_key: Some(self.write("client.key", &self.client.1)),
+                ..options
+            }
+        }
+
+        fn server_config(&self, require_client_cert: bool) -> ServerTlsConfig {
+            let config =
+                ServerTlsConfig::new().identity(Identity::from_pem(&self.server.0, &self.server.1));
+            match require_client_cert {
+                true => config.client_ca_root(Certificate::from_pem(&self.ca)),
+                false => config,
+            }
+        }
+    }
+
+    /// Serve a mock daemon over TLS, returning its address once it accepts
+    /// connections
+    async fn serve(tls: ServerTlsConfig) -> (String, JoinHandle<()>) {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("pick a free port");
+        let daemon = MockDaemon::new(MockConfig::default());
+        let server = tokio::spawn(async move {
+            mock::serve(addr, daemon, Duration::ZERO, Some(tls))
+                .await
+                .expect("serve mock daemon");
+        });
+        for _ in 0..100 {
+            if TcpStream::connect(addr).await.is_ok() {
+                return (format!("https://{}", addr), server);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("mock daemon did not accept connections");
+    }
+
+    /// Connect and make one call, which is where a refused client
+    /// certificate shows up under TLS 1.3
+    async fn call(address: &str, tls: TlsOptions) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let options = ClientOptions {
+            tls,
+            ..ClientOptions::default()
+        };
+        let mut client = DaemonClient::with_options(address.to_string(), options);
+        client.connect().await?;
+        client.get_status().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn connects_to_daemon_signed_by_configured_ca() {
+        let pki = Pki::generate();
+        let (address, server) = serve(pki.server_config(false)).await;
+
+        call(&address, pki.trusted()).await.expect("verified call");
+
+        // The system roots do not know the generated CA
+        let error = call(&address, TlsOptions::default())
+            .await
+            .expect_err("unknown issuer");
+        assert!(
+            matches!(
+                error.downcast_ref::<ConnectError>(),
+                Some(ConnectError::Handshake(_))
+            ),
+            "{}",
+            error
+        );
+        // Nor does the certificate name the address
+        let unnamed = TlsOptions {
+            server_name: None,
+            ..pki.trusted()
+        };
+        assert!(call(&address, unnamed).await.is_err());
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn presents_client_certificate_for_mutual_tls() {
+        let pki = Pki::generate();
+        let (address, server) = serve(pki.server_config(true)).await;
+
+        call(&address, pki.with_client_cert(pki.trusted()))
+            .await
+            .expect("mutual TLS call");
+        assert!(call(&address, pki.trusted()).await.is_err());
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn insecure_skip_verify_accepts_unknown_certificates() {
+        let pki = Pki::generate();
+        let (address, server) = serve(pki.server_config(true)).await;
+
+        let insecure = TlsOptions {
+            insecure_skip_verify: true,
+            ..TlsOptions::default()
+        };
+        call(&address, pki.with_client_cert(insecure.clone()))
+            .await
+            .expect("unverified call");
+        // The client certificate is still presented and checked
+        assert!(call(&address, insecure).await.is_err());
+        server.abort();
+    }
+}
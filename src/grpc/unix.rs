@@ -0,0 +1,64 @@
+use std::path::Path;
+
+#[cfg(unix)]
+pub(crate) use connector::UnixConnector;
+
+/// Placeholder URI for socket channels. Only its authority reaches the
+/// daemon, as the `:authority` header.
+#[cfg(unix)]
+pub(crate) const SOCKET_ORIGIN: &str = "http://localhost";
+
+/// Socket path of a `unix:` address.
+///
+/// Accepts `unix:///absolute/path` and `unix:path`, following the gRPC
+/// naming convention. Returns `None` for other addresses.
+pub fn socket_path(address: &str) -> Option<&Path> {
+    let path = address
+        .strip_prefix("unix://")
+        .or_else(|| address.strip_prefix("unix:"))?;
+    Some(Path::new(path))
+}
+
+#[cfg(unix)]
+mod connector {
+    use std::{
+        future::Future,
+        path::{Path, PathBuf},
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use hyper_util::rt::TokioIo;
+    use tokio::net::UnixStream;
+    use tonic::transport::Uri;
+    use tower_service::Service;
+
+    /// Connector that opens a Unix domain socket in place of a TCP connection
+    #[derive(Debug, Clone)]
+    pub(crate) struct UnixConnector {
+        path: PathBuf,
+    }
+
+    impl UnixConnector {
+        pub(crate) fn new(path: &Path) -> Self {
+            Self {
+                path: path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Service<Uri> for UnixConnector {
+        type Response = TokioIo<UnixStream>;
+        type Error = std::io::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _uri: Uri) -> Self::Future {
+            let path = self.path.clone();
+            Box::pin(async move { Ok(TokioIo::new(UnixStream::connect(path).await?)) })
+        }
+    }
+}
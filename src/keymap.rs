@@ -0,0 +1,1014 @@
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// Something a key does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Help,
+    NextPanel,
+    PrevPanel,
+    Connect,
+    Disconnect,
+    MetricsHistory,
+    Audit,
+    /// Go back to the fleet view
+    Fleet,
+    /// Control actions, sent without selecting them in the controls panel
+    Start,
+    Stop,
+    Restart,
+    Reload,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    /// Run the selected control action
+    Execute,
+    Search,
+    Filter,
+    Level,
+    Source,
+    Order,
+    Details,
+    ExportShown,
+    ExportAll,
+    NextMatch,
+    PrevMatch,
+    HistoryWindow,
+    Mark,
+    MarkAll,
+    BulkMenu,
+    CancelBulk,
+    /// Open the dashboard of the selected fleet member
+    Open,
+    /// Switch between parallel and rolling bulk actions
+    Mode,
+    GrowBatch,
+    ShrinkBatch,
+    /// Run the bulk action
+    Run,
+    /// Close a popup or overlay
+    Close,
+    /// Send a confirmed control action, or apply typed text
+    Confirm,
+    Cancel,
+    /// Switch a typed query between text and regex
+    ToggleRegex,
+    /// Delete the last typed character
+    DeleteChar,
+}
+
+impl Action {
+    pub const ALL: [Action; 45] = [
+        Action::Quit,
+        Action::Help,
+        Action::NextPanel,
+        Action::PrevPanel,
+        Action::Connect,
+        Action::Disconnect,
+        Action::MetricsHistory,
+        Action::Audit,
+        Action::Fleet,
+        Action::Start,
+        Action::Stop,
+        Action::Restart,
+        Action::Reload,
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::Execute,
+        Action::Search,
+        Action::Filter,
+        Action::Level,
+        Action::Source,
+        Action::Order,
+        Action::Details,
+        Action::ExportShown,
+        Action::ExportAll,
+        Action::NextMatch,
+        Action::PrevMatch,
+        Action::HistoryWindow,
+        Action::Mark,
+        Action::MarkAll,
+        Action::BulkMenu,
+        Action::CancelBulk,
+        Action::Open,
+        Action::Mode,
+        Action::GrowBatch,
+        Action::ShrinkBatch,
+        Action::Run,
+        Action::Close,
+        Action::Confirm,
+        Action::Cancel,
+        Action::ToggleRegex,
+        Action::DeleteChar,
+    ];
+
+    /// Name of the action in the `[keys]` table of the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Help => "help",
+            Self::NextPanel => "next_panel",
+            Self::PrevPanel => "prev_panel",
+            Self::Connect => "connect",
+            Self::Disconnect => "disconnect",
+            Self::MetricsHistory => "metrics_history",
+            Self::Audit => "audit",
+            Self::Fleet => "fleet",
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+            Self::Reload => "reload",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::PageUp => "page_up",
+            Self::PageDown => "page_down",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Execute => "execute",
+            Self::Search => "search",
+            Self::Filter => "filter",
+            Self::Level => "level",
+            Self::Source => "source",
+            Self::Order => "order",
+            Self::Details => "details",
+            Self::ExportShown => "export_shown",
+            Self::ExportAll => "export_all",
+            Self::NextMatch => "next_match",
+            Self::PrevMatch => "prev_match",
+            Self::HistoryWindow => "history_window",
+            Self::Mark => "mark",
+            Self::MarkAll => "mark_all",
+            Self::BulkMenu => "bulk_menu",
+            Self::CancelBulk => "cancel_bulk",
+            Self::Open => "open",
+            Self::Mode => "mode",
+            Self::GrowBatch => "grow_batch",
+            Self::ShrinkBatch => "shrink_batch",
+            Self::Run => "run",
+            Self::Close => "close",
+            Self::Confirm => "confirm",
+            Self::Cancel => "cancel",
+            Self::ToggleRegex => "toggle_regex",
+            Self::DeleteChar => "delete_char",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// Where a set of key bindings applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// Anywhere on the dashboard, before the panel and view keys
+    Global,
+    ControlsPanel,
+    StatusPanel,
+    LogsPanel,
+    MetricsHistory,
+    Audit,
+    Fleet,
+    BulkMenu,
+    /// The popup asking to confirm a control action
+    Confirm,
+    /// Typing a search or filter query, or a daemon name to confirm
+    Input,
+    LogDetail,
+    Help,
+}
+
+impl Context {
+    pub const ALL: [Context; 12] = [
+        Context::Global,
+        Context::ControlsPanel,
+        Context::StatusPanel,
+        Context::LogsPanel,
+        Context::MetricsHistory,
+        Context::Audit,
+        Context::Fleet,
+        Context::BulkMenu,
+        Context::Confirm,
+        Context::Input,
+        Context::LogDetail,
+        Context::Help,
+    ];
+
+    /// Heading of the context's group in the help overlay
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Global => "Global",
+            Self::ControlsPanel => "Controls panel",
+            Self::StatusPanel => "Status panel",
+            Self::LogsPanel => "Logs panel",
+            Self::MetricsHistory => "Metrics history",
+            Self::Audit => "Audit trail",
+            Self::Fleet => "Fleet view",
+            Self::BulkMenu => "Bulk action menu",
+            Self::Confirm => "Confirmation popup",
+            Self::Input => "Text input",
+            Self::LogDetail => "Log entry popup",
+            Self::Help => "Help",
+        }
+    }
+
+    /// Name of the context's table under `[keys]` in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::ControlsPanel => "controls",
+            Self::StatusPanel => "status",
+            Self::LogsPanel => "logs",
+            Self::MetricsHistory => "metrics_history",
+            Self::Audit => "audit",
+            Self::Fleet => "fleet",
+            Self::BulkMenu => "bulk_menu",
+            Self::Confirm => "confirm",
+            Self::Input => "input",
+            Self::LogDetail => "log_detail",
+            Self::Help => "help",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|context| context.name() == name)
+    }
+}
+
+/// Contexts whose keys are live at the same time, in lookup order. A key
+/// sequence may only mean one thing within each of these.
+const LAYERS: [&[Context]; 11] = [
+    &[Context::Global, Context::ControlsPanel],
+    &[Context::Global, Context::StatusPanel],
+    &[Context::Global, Context::LogsPanel],
+    &[Context::Global, Context::MetricsHistory],
+    &[Context::Global, Context::Audit],
+    &[Context::Fleet],
+    &[Context::BulkMenu],
+    &[Context::Confirm],
+    &[Context::Input],
+    &[Context::LogDetail],
+    &[Context::Help],
+];
+
+/// A key press, with the modifiers that must be held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c))
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Key as pressed in the terminal. Shift is dropped, since it is
+    /// already part of the character typed or of Shift-Tab.
+    pub fn pressed(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {
+            code,
+            modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    /// Character the key types into a text input, if it is a plain one
+    pub fn typed_char(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    /// Name of the key as shown to the user, e.g. `Ctrl-c` or `PgUp`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::BackTab => f.write_str("Shift-Tab"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parse a key such as `q`, `ctrl-r`, `alt-shift-x`, `pgdn` or `f5`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut shift = false;
+        let mut rest = s;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            // A lone `-` is a key, not a separator
+            let prefix = ["ctrl-", "alt-", "shift-"]
+                .into_iter()
+                .find(|prefix| lower.starts_with(prefix) && rest.len() > prefix.len());
+            match prefix {
+                Some("ctrl-") => modifiers |= KeyModifiers::CONTROL,
+                Some("alt-") => modifiers |= KeyModifiers::ALT,
+                Some(_) => shift = true,
+                None => break,
+            }
+            rest = &rest[prefix.map_or(0, str::len)..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", s)),
+                },
+            },
+        };
+        let code = match (shift, code) {
+            (false, code) => code,
+            (true, KeyCode::Tab) => KeyCode::BackTab,
+            (true, KeyCode::Char(c)) if c.is_alphabetic() => {
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+            }
+            (true, _) => return Err(format!("'{}': shift only combines with letters and tab", s)),
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+/// Keys pressed one after another to trigger an action, such as `g g`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub keys: Vec<Key>,
+}
+
+impl From<Key> for Chord {
+    fn from(key: Key) -> Self {
+        Self { keys: vec![key] }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    /// Parse space-separated keys, e.g. `ctrl-x r`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let keys = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Key>, String>>()?;
+        if keys.is_empty() {
+            return Err("empty key".to_string());
+        }
+        Ok(Self { keys })
+    }
+}
+
+/// Keys bound to an action in one context
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub action: Action,
+    /// Any of these triggers the action; none leaves it unbound
+    pub chords: Vec<Chord>,
+    pub description: &'static str,
+}
+
+impl Binding {
+    /// Keys as shown to the user, leaving out the uppercase twin of a
+    /// letter that is bound too
+    pub fn label(&self) -> String {
+        let lowercase_bound = |c: char, modifiers| {
+            self.chords.iter().any(|chord| {
+                chord.keys
+                    == [Key {
+                        code: KeyCode::Char(c.to_ascii_lowercase()),
+                        modifiers,
+                    }]
+            })
+        };
+        let shown: Vec<String> = self
+            .chords
+            .iter()
+            .filter(|chord| match chord.keys.as_slice() {
+                [Key {
+                    code: KeyCode::Char(c),
+                    modifiers,
+                }] if c.is_uppercase() => !lowercase_bound(*c, *modifiers),
+                _ => true,
+            })
+            .map(Chord::to_string)
+            .collect();
+        shown.join("/")
+    }
+}
+
+/// Result of looking up keys pressed so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    /// The keys trigger an action of this context
+    Action(Context, Action),
+    /// The keys start a chord; wait for the next key
+    Prefix,
+    /// The keys mean nothing here
+    None,
+}
+
+/// Every key binding, grouped by where it applies.
+///
+/// Key handling looks actions up here, and the help overlay and footer are
+/// drawn from it, so they always agree with the keys the user configured.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    groups: Vec<(Context, Vec<Binding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+        use KeyCode::{
+            BackTab, Backspace, End, Enter, Esc, Home, PageDown as PgDn, PageUp as PgUp, Tab,
+        };
+        let key = Key::new;
+        let char = Key::char;
+        let bind = |action, keys: &[Key], description| Binding {
+            action,
+            chords: keys.iter().copied().map(Chord::from).collect(),
+            description,
+        };
+        let up = |description| bind(Up, &[char('k'), key(KeyCode::Up)], description);
+        let down = |description| bind(Down, &[char('j'), key(KeyCode::Down)], description);
+        let quit = || bind(Quit, &[char('q'), char('Q'), Key::ctrl('c')], "Quit");
+        // Popups leave plain keys to their own actions
+        let interrupt = || bind(Quit, &[Key::ctrl('c')], "Quit");
+        let help = || bind(Help, &[char('?')], "Show this help");
+        let scrolling = || {
+            vec![
+                bind(Up, &[char('k'), key(KeyCode::Up)], "Scroll up"),
+                bind(Down, &[char('j'), key(KeyCode::Down)], "Scroll down"),
+                bind(PageUp, &[key(PgUp)], "Scroll up a page"),
+                bind(PageDown, &[key(PgDn)], "Scroll down a page"),
+                bind(Top, &[key(Home), char('g')], "Scroll to the top"),
+                bind(Bottom, &[key(End), char('G')], "Scroll to the bottom"),
+            ]
+        };
+
+        let mut log_detail = scrolling();
+        log_detail.push(bind(
+            Close,
+            &[key(Esc), key(Enter), char('q')],
+            "Close the popup",
+        ));
+        log_detail.push(interrupt());
+        let mut help_overlay = scrolling();
+        help_overlay.push(bind(
+            Close,
+            &[key(Esc), char('?'), char('q')],
+            "Close the help",
+        ));
+        help_overlay.push(interrupt());
+
+        Self {
+            groups: vec![
+                (
+                    Context::Global,
+                    vec![
+                        quit(),
+                        help(),
+                        bind(NextPanel, &[key(Tab)], "Focus the next panel"),
+                        bind(PrevPanel, &[key(BackTab)], "Focus the previous panel"),
+                        bind(Connect, &[char('c'), char('C')], "Connect to the daemon"),
+                        bind(
+                            Disconnect,
+                            &[char('d'), char('D')],
+                            "Disconnect from the daemon",
+                        ),
+                        bind(
+                            MetricsHistory,
+                            &[char('m'), char('M')],
+                            "Toggle the metrics history",
+                        ),
+                        bind(Audit, &[char('a'), char('A')], "Toggle the audit trail"),
+                        bind(Fleet, &[key(Esc)], "Back to the fleet view (with --fleet)"),
+                        bind(Start, &[], "Start the daemon"),
+                        bind(Stop, &[], "Stop the daemon"),
+                        bind(Restart, &[], "Restart the daemon"),
+                        bind(Reload, &[], "Reload the daemon's configuration"),
+                    ],
+                ),
+                (
+                    Context::ControlsPanel,
+                    vec![
+                        up("Select the previous action"),
+                        down("Select the next action"),
+                        bind(Execute, &[key(Enter)], "Run the selected action"),
+                    ],
+                ),
+                (Context::StatusPanel, Vec::new()),
+                (
+                    Context::LogsPanel,
+                    vec![
+                        up("Move the cursor up"),
+                        down("Move the cursor down"),
+                        bind(PageUp, &[key(PgUp)], "Move the cursor up a page"),
+                        bind(PageDown, &[key(PgDn)], "Move the cursor down a page"),
+                        bind(Top, &[key(Home), char('g')], "Go to the oldest entry"),
+                        bind(
+                            Bottom,
+                            &[key(End), char('G')],
+                            "Go to the newest entry and follow new ones",
+                        ),
+                        bind(Details, &[key(Enter)], "Show the entry under the cursor"),
+                        bind(Search, &[char('/')], "Search"),
+                        bind(NextMatch, &[char('n')], "Next search match"),
+                        bind(PrevMatch, &[char('N')], "Previous search match"),
+                        bind(Filter, &[char('f'), char('F')], "Filter by text or regex"),
+                        bind(Level, &[char('l'), char('L')], "Cycle the minimum level"),
+                        bind(Source, &[char('s'), char('S')], "Cycle the source shown"),
+                        bind(Order, &[char('o'), char('O')], "Sort by time or level"),
+                        bind(ExportShown, &[char('e')], "Export the entries shown"),
+                        bind(ExportAll, &[char('E')], "Export every entry"),
+                    ],
+                ),
+                (
+                    Context::MetricsHistory,
+                    vec![bind(
+                        HistoryWindow,
+                        &[char('w'), char('W')],
+                        "Cycle the time window",
+                    )],
+                ),
+                (
+                    Context::Audit,
+                    vec![
+                        up("Select a newer command"),
+                        down("Select an older command"),
+                    ],
+                ),
+                (
+                    Context::Fleet,
+                    vec![
+                        quit(),
+                        help(),
+                        up("Select the previous daemon"),
+                        down("Select the next daemon"),
+                        bind(Open, &[key(Enter)], "Open the daemon's dashboard"),
+                        bind(Mark, &[char(' ')], "Mark the daemon"),
+                        bind(MarkAll, &[char('a')], "Mark every daemon"),
+                        bind(
+                            BulkMenu,
+                            &[char('b')],
+                            "Run an action on the marked daemons",
+                        ),
+                        bind(CancelBulk, &[key(Esc)], "Cancel the running bulk action"),
+                    ],
+                ),
+                (
+                    Context::BulkMenu,
+                    vec![
+                        up("Select the previous action"),
+                        down("Select the next action"),
+                        bind(Mode, &[char('m')], "Switch between parallel and rolling"),
+                        bind(GrowBatch, &[char('+'), char('=')], "Grow the batch"),
+                        bind(ShrinkBatch, &[char('-')], "Shrink the batch"),
+                        bind(Run, &[key(Enter)], "Run the action"),
+                        bind(Close, &[key(Esc)], "Close the menu"),
+                        interrupt(),
+                    ],
+                ),
+                (
+                    Context::Confirm,
+                    vec![
+                        bind(
+                            Confirm,
+                            &[char('y'), char('Y'), key(Enter)],
+                            "Send the action",
+                        ),
+                        bind(
+                            Cancel,
+                            &[char('n'), char('N'), key(Esc)],
+                            "Cancel the action",
+                        ),
+                        interrupt(),
+                    ],
+                ),
+                (
+                    Context::Input,
+                    vec![
+                        bind(
+                            Confirm,
+                            &[key(Enter)],
+                            "Apply the query, or confirm the typed daemon name",
+                        ),
+                        bind(Cancel, &[key(Esc)], "Cancel"),
+                        bind(
+                            ToggleRegex,
+                            &[key(Tab)],
+                            "Switch the query between text and regex",
+                        ),
+                        bind(DeleteChar, &[key(Backspace)], "Delete the last character"),
+                        interrupt(),
+                    ],
+                ),
+                (Context::LogDetail, log_detail),
+                (Context::Help, help_overlay),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// Action triggered by `keys` in the first of `contexts` binding them
+    pub fn lookup(&self, contexts: &[Context], keys: &[Key]) -> Lookup {
+        let mut prefix = false;
+        for &context in contexts {
+            for binding in self.bindings(context) {
+                for chord in &binding.chords {
+                    if chord.keys == keys {
+                        return Lookup::Action(context, binding.action);
+                    }
+                    prefix |= chord.keys.starts_with(keys);
+                }
+            }
+        }
+        if prefix {
+            Lookup::Prefix
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// Bindings of a context, in the order they are listed in the help
+    pub fn bindings(&self, context: Context) -> &[Binding] {
+        self.groups
+            .iter()
+            .find(|(c, _)| *c == context)
+            .map_or(&[], |(_, bindings)| bindings)
+    }
+
+    /// Every group of bindings, in the order they are listed in the help
+    pub fn groups(&self) -> impl Iterator<Item = (Context, &[Binding])> {
+        self.groups
+            .iter()
+            .map(|(context, bindings)| (*context, bindings.as_slice()))
+    }
+
+    /// First keys bound to each of `actions` in a context, for short hints
+    /// such as `j/k`, or `None` if none of them is bound
+    pub fn hint(&self, context: Context, actions: &[Action]) -> Option<String> {
+        let bindings = self.bindings(context);
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| bindings.iter().find(|b| b.action == *action))
+            .filter_map(|binding| binding.chords.first())
+            .map(Chord::to_string)
+            .collect();
+        (!keys.is_empty()).then(|| keys.join("/"))
+    }
+
+    /// Replace the keys of an action in one context, or in every context
+    /// that has it when `context` is `None`
+    pub fn rebind(
+        &mut self,
+        context: Option<Context>,
+        action: Action,
+        chords: Vec<Chord>,
+    ) -> Result<(), String> {
+        let mut found = false;
+        for (c, bindings) in &mut self.groups {
+            if context.is_some_and(|context| context != *c) {
+                continue;
+            }
+            for binding in bindings.iter_mut().filter(|b| b.action == action) {
+                binding.chords = chords.clone();
+                found = true;
+            }
+        }
+        match (found, context) {
+            (true, _) => Ok(()),
+            (false, Some(context)) => Err(format!(
+                "{} has no '{}' action",
+                context.name(),
+                action.name()
+            )),
+            (false, None) => Err(format!("'{}' is not bound anywhere", action.name())),
+        }
+    }
+
+    /// Check that no keys mean two things at once, that no chord is hidden
+    /// behind a shorter binding of its first keys, and that no text input
+    /// key is a character that would be typed instead
+    pub fn check(&self) -> Result<(), String> {
+        let mut conflicts = BTreeSet::new();
+        for binding in self.bindings(Context::Input) {
+            for chord in &binding.chords {
+                if chord.keys.first().and_then(Key::typed_char).is_some() {
+                    conflicts.insert(format!(
+                        "'{}' ({} in {}) is typed as text",
+                        chord,
+                        binding.action.name(),
+                        Context::Input.name()
+                    ));
+                }
+            }
+        }
+        for layer in LAYERS {
+            let chords: Vec<(Context, Action, &Chord)> = layer
+                .iter()
+                .flat_map(|&context| {
+                    self.bindings(context).iter().flat_map(move |binding| {
+                        binding
+                            .chords
+                            .iter()
+                            .map(move |chord| (context, binding.action, chord))
+                    })
+                })
+                .collect();
+            for (i, (context, action, chord)) in chords.iter().enumerate() {
+                for (other_context, other_action, other) in &chords[i + 1..] {
+                    if (context, action) == (other_context, other_action) {
+                        continue;
+                    }
+                    let describe = |chord: &Chord, context: &Context, action: &Action| {
+                        format!("'{}' ({} in {})", chord, action.name(), context.name())
+                    };
+                    let first = describe(chord, context, action);
+                    let second = describe(other, other_context, other_action);
+                    if chord == other {
+                        conflicts.insert(format!(
+                            "'{}' is bound to both {} in {} and {} in {}",
+                            chord,
+                            action.name(),
+                            context.name(),
+                            other_action.name(),
+                            other_context.name()
+                        ));
+                    } else if other.keys.starts_with(&chord.keys) {
+                        conflicts.insert(format!("{} hides {}", first, second));
+                    } else if chord.keys.starts_with(&other.keys) {
+                        conflicts.insert(format!("{} hides {}", second, first));
+                    }
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "conflicting key bindings: {}",
+                conflicts.into_iter().collect::<Vec<_>>().join("; ")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Key {
+        s.parse().expect("valid key")
+    }
+
+    fn chords(s: &str) -> Vec<Chord> {
+        vec![s.parse().expect("valid chord")]
+    }
+
+    #[test]
+    fn named_keys() {
+        assert_eq!(key("enter"), Key::new(KeyCode::Enter));
+        assert_eq!(key("Esc"), Key::new(KeyCode::Esc));
+        assert_eq!(key("pgdn"), Key::new(KeyCode::PageDown));
+        assert_eq!(key("f5"), Key::new(KeyCode::F(5)));
+        assert_eq!(key("space"), Key::char(' '));
+        assert_eq!(key("backtab"), Key::new(KeyCode::BackTab));
+        assert_eq!(key("-"), Key::char('-'));
+    }
+
+    #[test]
+    fn modifiers() {
+        assert_eq!(key("ctrl-r"), Key::ctrl('r'));
+        assert_eq!(key("CTRL-r"), Key::ctrl('r'));
+        assert_eq!(key("ctrl--"), Key::ctrl('-'));
+        assert_eq!(
+            key("alt-shift-x"),
+            Key {
+                code: KeyCode::Char('X'),
+                modifiers: KeyModifiers::ALT,
+            }
+        );
+        assert_eq!(key("shift-tab"), Key::new(KeyCode::BackTab));
+        assert_eq!(key("ctrl-c").to_string(), "Ctrl-c");
+        assert_eq!(key("shift-tab").to_string(), "Shift-Tab");
+    }
+
+    #[test]
+    fn bad_keys() {
+        assert_eq!("".parse::<Key>(), Err("unknown key ''".to_string()));
+        assert_eq!("foo".parse::<Key>(), Err("unknown key 'foo'".to_string()));
+        assert_eq!("f25".parse::<Key>(), Err("unknown key 'f25'".to_string()));
+        assert_eq!(
+            "ctrl-".parse::<Key>(),
+            Err("unknown key 'ctrl-'".to_string())
+        );
+        assert_eq!(
+            "shift-1".parse::<Key>(),
+            Err("'shift-1': shift only combines with letters and tab".to_string())
+        );
+    }
+
+    #[test]
+    fn chords_of_several_keys() {
+        let chord: Chord = "ctrl-x   r".parse().expect("valid chord");
+        assert_eq!(chord.keys, vec![Key::ctrl('x'), Key::char('r')]);
+        assert_eq!(chord.to_string(), "Ctrl-x r");
+        assert_eq!(" ".parse::<Chord>(), Err("empty key".to_string()));
+        assert_eq!(
+            "g bogus".parse::<Chord>(),
+            Err("unknown key 'bogus'".to_string())
+        );
+    }
+
+    #[test]
+    fn chords_wait_for_their_last_key() {
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(Some(Context::LogsPanel), Action::Top, chords("g g"))
+            .expect("rebind");
+        let contexts = [Context::Global, Context::LogsPanel];
+        assert_eq!(keymap.lookup(&contexts, &[key("g")]), Lookup::Prefix);
+        assert_eq!(
+            keymap.lookup(&contexts, &[key("g"), key("g")]),
+            Lookup::Action(Context::LogsPanel, Action::Top)
+        );
+        assert_eq!(
+            keymap.lookup(&contexts, &[key("g"), key("x")]),
+            Lookup::None
+        );
+        assert_eq!(keymap.check(), Ok(()));
+    }
+
+    #[test]
+    fn labels_hide_uppercase_twins() {
+        let keymap = Keymap::default();
+        let quit = &keymap.bindings(Context::Global)[0];
+        assert_eq!(quit.label(), "q/Ctrl-c");
+        assert_eq!(
+            keymap.hint(Context::Fleet, &[Action::Down, Action::Up]),
+            Some("j/k".to_string())
+        );
+    }
+
+    #[test]
+    fn default_keymap_has_no_conflicts() {
+        assert_eq!(Keymap::default().check(), Ok(()));
+    }
+
+    #[test]
+    fn keys_bound_twice_in_a_layer() {
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(Some(Context::Global), Action::Help, chords("l"))
+            .expect("rebind");
+        assert_eq!(
+            keymap.check(),
+            Err(
+                "conflicting key bindings: 'l' is bound to both help in global and \
+                 level in logs"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn chords_hidden_by_a_prefix_in_another_context() {
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(Some(Context::LogsPanel), Action::Search, chords("c s"))
+            .expect("rebind");
+        assert_eq!(
+            keymap.check(),
+            Err("conflicting key bindings: 'c' (connect in global) hides \
+                 'c s' (search in logs)"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn same_keys_in_separate_layers() {
+        let mut keymap = Keymap::default();
+        // The fleet view and the dashboard are never shown together
+        keymap
+            .rebind(Some(Context::Fleet), Action::BulkMenu, chords("c"))
+            .expect("rebind");
+        assert_eq!(keymap.check(), Ok(()));
+    }
+
+    #[test]
+    fn rebinding_quit_everywhere() {
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(None, Action::Quit, chords("ctrl-q"))
+            .expect("rebind");
+        for context in [
+            Context::Global,
+            Context::Confirm,
+            Context::Input,
+            Context::Help,
+        ] {
+            assert_eq!(
+                keymap.lookup(&[context], &[key("ctrl-c")]),
+                Lookup::None,
+                "{}",
+                context.name()
+            );
+        }
+        assert_eq!(keymap.check(), Ok(()));
+
+        keymap
+            .rebind(None, Action::Quit, chords("q"))
+            .expect("rebind");
+        assert_eq!(
+            keymap.check(),
+            Err(
+                "conflicting key bindings: 'q' (quit in input) is typed as text; 'q' is \
+                 bound to both close in help and quit in help; 'q' is bound to both close \
+                 in log_detail and quit in log_detail"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rebinding_unknown_actions() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.rebind(Some(Context::StatusPanel), Action::Up, chords("x")),
+            Err("status has no 'up' action".to_string())
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Shared library for the daemon controller binaries: the gRPC client and an
+//! in-process mock daemon for development and tests.
+
+pub mod grpc;
+pub mod mock;
@@ -0,0 +1,101 @@
+use std::collections::{vec_deque, VecDeque};
+
+use crate::app::LogEntry;
+
+/// Log entries kept unless configured otherwise
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Log entries shown in the logs panel, capped to a fixed number.
+///
+/// Once full, each new entry evicts the oldest one.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Change the capacity, evicting the oldest entries if over it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+    }
+
+    /// Append an entry, returning the one evicted to make room, if any
+    pub fn push(&mut self, entry: LogEntry) -> Option<LogEntry> {
+        let evicted = if self.entries.len() >= self.capacity {
+            self.entries.pop_front()
+        } else {
+            None
+        };
+        self.entries.push_back(entry);
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Entries oldest first
+    pub fn iter(&self) -> vec_deque::Iter<'_, LogEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: None,
+            level: Default::default(),
+            origin: Default::default(),
+            message: message.to_string(),
+            source: String::new(),
+        }
+    }
+
+    fn messages(buffer: &LogBuffer) -> Vec<&str> {
+        buffer.iter().map(|entry| entry.message.as_str()).collect()
+    }
+
+    #[test]
+    fn evicts_the_oldest_at_capacity() {
+        let mut buffer = LogBuffer::new(2);
+        assert!(buffer.push(entry("one")).is_none());
+        assert!(buffer.push(entry("two")).is_none());
+        let evicted = buffer.push(entry("six")).expect("evicted");
+        assert_eq!(evicted.message, "one");
+        assert_eq!(messages(&buffer), ["two", "six"]);
+    }
+
+    #[test]
+    fn shrinking_evicts_the_oldest() {
+        let mut buffer = LogBuffer::new(3);
+        for message in ["one", "two", "six"] {
+            buffer.push(entry(message));
+        }
+        buffer.set_capacity(1);
+        assert_eq!(messages(&buffer), ["six"]);
+
+        // At least one entry is always kept
+        buffer.set_capacity(0);
+        buffer.push(entry("ten"));
+        assert_eq!(messages(&buffer), ["ten"]);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::app::{LogEntry, LogOrigin};
+use daemon_controller::grpc::daemon::LogLevel;
+
+/// File format of exported logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Entries as shown in the logs panel, but with full timestamps
+    Text,
+    /// One JSON object per line
+    Jsonl,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Format implied by a file extension, defaulting to text
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("jsonl" | "json" | "ndjson") => Self::Jsonl,
+            Some("csv") => Self::Csv,
+            _ => Self::Text,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "log",
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// Where and how the logs panel is exported
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// File to write, or `None` to name one after the current time
+    pub path: Option<PathBuf>,
+    /// Format to write, or `None` to go by the file extension
+    pub format: Option<ExportFormat>,
+}
+
+impl ExportOptions {
+    /// File and format the next export goes to
+    pub fn target(&self) -> (PathBuf, ExportFormat) {
+        match &self.path {
+            Some(path) => (
+                path.clone(),
+                self.format.unwrap_or_else(|| ExportFormat::from_path(path)),
+            ),
+            None => {
+                let format = self.format.unwrap_or(ExportFormat::Text);
+                let name = format!(
+                    "daemon-controller-logs-{}.{}",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S"),
+                    format.extension()
+                );
+                (PathBuf::from(name), format)
+            }
+        }
+    }
+}
+
+/// Write `entries` to `path`, replacing the file
+pub fn export<'a>(
+    entries: impl IntoIterator<Item = &'a LogEntry>,
+    path: &Path,
+    format: ExportFormat,
+) -> io::Result<usize> {
+    let mut out = BufWriter::new(File::create(path)?);
+    if format == ExportFormat::Csv {
+        writeln!(out, "timestamp,level,origin,source,message")?;
+    }
+    let mut count = 0;
+    for entry in entries {
+        let timestamp = entry.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default();
+        match format {
+            ExportFormat::Text => {
+                let mut prefix = format!("{} {:<5} ", timestamp, entry.level.label());
+                if !entry.source.is_empty() {
+                    prefix.push_str(&format!("[{}] ", entry.source));
+                }
+                let mut lines = entry.message.lines();
+                writeln!(out, "{}{}", prefix, lines.next().unwrap_or_default())?;
+                // Later lines start under the first, as in the logs panel
+                let indent = " ".repeat(prefix.chars().count());
+                for line in lines {
+                    writeln!(out, "{}{}", indent, line)?;
+                }
+            }
+            ExportFormat::Jsonl => writeln!(out, "{}", to_json(entry))?,
+            ExportFormat::Csv => writeln!(
+                out,
+                "{},{},{},{},{}",
+                csv_field(&timestamp),
+                entry.level.label(),
+                entry.origin.label(),
+                csv_field(&entry.source),
+                csv_field(&entry.message)
+            )?,
+        }
+        count += 1;
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// Entry as a JSON object, as written to JSON lines exports and log files
+pub fn to_json(entry: &LogEntry) -> serde_json::Value {
+    json!({
+        "timestamp": entry.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        "level": entry.level.label(),
+        "origin": entry.origin.label(),
+        "source": entry.source,
+        "message": entry.message,
+    })
+}
+
+/// Entry as read back from a JSON line
+#[derive(Deserialize)]
+struct JsonEntry {
+    #[serde(default)]
+    timestamp: String,
+    level: String,
+    #[serde(default)]
+    origin: String,
+    #[serde(default)]
+    source: String,
+    message: String,
+}
+
+/// Parse a line written by [`to_json`], or `None` if it is not one
+pub fn from_json(line: &str) -> Option<LogEntry> {
+    let entry: JsonEntry = serde_json::from_str(line).ok()?;
+    Some(LogEntry {
+        timestamp: chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Local)),
+        // Levels this build does not know are shown as info, as for the daemon
+        level: LogLevel::from_label(&entry.level).unwrap_or(LogLevel::Info),
+        origin: LogOrigin::from_label(&entry.origin).unwrap_or_default(),
+        message: entry.message,
+        source: entry.source,
+    })
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: LogLevel, source: &str, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: Some(chrono::Local::now()),
+            level,
+            origin: LogOrigin::Daemon,
+            message: message.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn csv_fields() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("one\ntwo"), "\"one\ntwo\"");
+        assert_eq!(csv_field("one\r\ntwo"), "\"one\r\ntwo\"");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let original = LogEntry {
+            origin: LogOrigin::Controller,
+            ..entry(LogLevel::Warn, "", "line one\nline \"two\"")
+        };
+        let line = to_json(&original).to_string();
+        let parsed = from_json(&line).expect("valid line");
+        assert_eq!(parsed.timestamp, original.timestamp);
+        assert_eq!(parsed.level, LogLevel::Warn);
+        assert_eq!(parsed.origin, LogOrigin::Controller);
+        assert_eq!(parsed.source, "");
+        assert_eq!(parsed.message, original.message);
+
+        // Fields keep their order
+        let no_time = LogEntry {
+            timestamp: None,
+            ..entry(LogLevel::Info, "http", "ok")
+        };
+        assert_eq!(
+            to_json(&no_time).to_string(),
+            r#"{"timestamp":"","level":"INFO","origin":"daemon","source":"http","message":"ok"}"#
+        );
+        assert!(from_json(&to_json(&no_time).to_string())
+            .expect("valid line")
+            .timestamp
+            .is_none());
+    }
+
+    #[test]
+    fn json_lines_that_do_not_parse() {
+        assert!(from_json("").is_none());
+        assert!(from_json("not json").is_none());
+        assert!(from_json(r#"{"level":"INFO"}"#).is_none());
+
+        // Optional fields may be missing
+        let entry = from_json(r#"{"level":"debug","message":"hi"}"#).expect("valid line");
+        assert_eq!(entry.level, LogLevel::Debug);
+        assert_eq!(entry.origin, LogOrigin::Daemon);
+        assert!(entry.timestamp.is_none());
+
+        let entry = from_json(r#"{"level":"TRACE","message":"hi"}"#).expect("valid line");
+        assert_eq!(entry.level, LogLevel::Info);
+    }
+
+    #[test]
+    fn formats_from_paths() {
+        let format = |path: &str| ExportFormat::from_path(Path::new(path));
+        assert_eq!(format("logs.jsonl"), ExportFormat::Jsonl);
+        assert_eq!(format("logs.JSON"), ExportFormat::Jsonl);
+        assert_eq!(format("logs.ndjson"), ExportFormat::Jsonl);
+        assert_eq!(format("out/logs.csv"), ExportFormat::Csv);
+        assert_eq!(format("logs.log"), ExportFormat::Text);
+        assert_eq!(format("logs"), ExportFormat::Text);
+        assert_eq!(format(".csv"), ExportFormat::Text);
+    }
+
+    #[test]
+    fn text_export_indents_continuation_lines() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("logs.log");
+        let entries = [
+            LogEntry {
+                timestamp: None,
+                ..entry(LogLevel::Error, "db", "query failed\n  at orders.rs:12\r\n")
+            },
+            LogEntry {
+                timestamp: None,
+                ..entry(LogLevel::Info, "", "")
+            },
+        ];
+        assert_eq!(
+            export(&entries, &path, ExportFormat::Text).expect("export"),
+            2
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read export"),
+            concat!(
+                " ERROR [db] query failed\n",
+                "              at orders.rs:12\n",
+                " INFO  \n",
+            )
+        );
+    }
+
+    #[test]
+    fn csv_export() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("logs.csv");
+        let entries = [
+            LogEntry {
+                timestamp: None,
+                ..entry(LogLevel::Error, "db", "query failed, retrying")
+            },
+            LogEntry {
+                timestamp: None,
+                ..entry(LogLevel::Info, "http", "ok")
+            },
+        ];
+        assert_eq!(
+            export(&entries, &path, ExportFormat::Csv).expect("export"),
+            2
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read export"),
+            "timestamp,level,origin,source,message\n\
+             ,ERROR,daemon,db,\"query failed, retrying\"\n\
+             ,INFO,daemon,http,ok\n"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+/// Structured fields of a log message, in the order they appear.
+///
+/// A message that is a JSON object yields its top-level members; any other
+/// message yields its `key=value` pairs, where values may be double-quoted
+/// to include spaces.
+pub fn parse(message: &str) -> Vec<(String, String)> {
+    let trimmed = message.trim();
+    if trimmed.starts_with('{') {
+        if let Ok(serde_json::Value::Object(object)) = serde_json::from_str(trimmed) {
+            return object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        value => value.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect();
+        }
+    }
+    parse_pairs(message)
+}
+
+fn parse_pairs(message: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut rest = message;
+    while let Some(eq) = rest.find('=') {
+        // The key is the run of key characters right before the `=`
+        let key_start = rest[..eq]
+            .char_indices()
+            .rfind(|&(_, c)| !is_key_char(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let key = &rest[key_start..eq];
+        let after = &rest[eq + 1..];
+        let (value, remainder) = match after.strip_prefix('"') {
+            Some(quoted) => split_quoted(quoted),
+            None => {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                (after[..end].to_string(), &after[end..])
+            }
+        };
+        if !key.is_empty() {
+            fields.push((key.to_string(), value));
+        }
+        rest = remainder;
+    }
+    fields
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Unescape a double-quoted value, returning it and the text after the
+/// closing quote
+fn split_quoted(s: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &s[i + 1..]),
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            c => value.push(c),
+        }
+    }
+    // Unterminated: take everything
+    (value, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn key_value_pairs() {
+        assert_eq!(
+            parse("request done method=GET status=200"),
+            pairs(&[("method", "GET"), ("status", "200")])
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert_eq!(
+            parse(r#"msg="hello \"big\" world" user=bob"#),
+            pairs(&[("msg", r#"hello "big" world"#), ("user", "bob")])
+        );
+        assert_eq!(
+            parse(r#"msg="unterminated"#),
+            pairs(&[("msg", "unterminated")])
+        );
+    }
+
+    #[test]
+    fn multi_byte_keys_and_values() {
+        assert_eq!(parse("result→x=1"), pairs(&[("x", "1")]));
+        assert_eq!(
+            parse("größe=groß ünits=€"),
+            pairs(&[("größe", "groß"), ("ünits", "€")])
+        );
+        assert_eq!(parse("→=1"), Vec::new());
+    }
+
+    #[test]
+    fn stray_equals_sign() {
+        assert_eq!(parse("a = b"), Vec::new());
+        assert_eq!(parse("x=1 == y=2"), pairs(&[("x", "1"), ("y", "2")]));
+        assert_eq!(parse("trailing="), pairs(&[("trailing", "")]));
+    }
+
+    #[test]
+    fn json_messages() {
+        assert_eq!(
+            parse(r#"{"level":"info","count":3,"ok":true,"tags":["a"]}"#),
+            pairs(&[
+                ("level", "info"),
+                ("count", "3"),
+                ("ok", "true"),
+                ("tags", r#"["a"]"#)
+            ])
+        );
+        // Not an object, so read as key=value text
+        assert_eq!(parse("{not json a=1"), pairs(&[("a", "1")]));
+    }
+}
@@ -0,0 +1,369 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc as std_mpsc,
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use tokio::sync::mpsc;
+
+use crate::app::LogEntry;
+use crate::events::Event;
+use crate::log_export;
+use daemon_controller::grpc::daemon;
+
+/// Size past which the log file is rotated unless configured otherwise
+pub const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Rotated files kept unless configured otherwise
+pub const DEFAULT_KEEP: usize = 5;
+
+/// Where streamed daemon logs are saved and when the file is rotated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileOptions {
+    pub path: PathBuf,
+    /// Size past which the file is rotated, or `None` to never rotate by size
+    pub max_size: Option<u64>,
+    /// Age past which the file is rotated, or `None` to never rotate by age
+    pub max_age: Option<Duration>,
+    /// Rotated files kept; older ones are deleted
+    pub keep: usize,
+    /// Whether rotated files are gzip-compressed
+    pub compress: bool,
+}
+
+impl LogFileOptions {
+    /// Default rotation of a log file at `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_size: Some(DEFAULT_MAX_SIZE),
+            max_age: None,
+            keep: DEFAULT_KEEP,
+            compress: true,
+        }
+    }
+
+    /// Path of the `n`th most recent rotated file, e.g. `daemon.jsonl.1.gz`
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        if self.compress {
+            name.push(".gz");
+        }
+        PathBuf::from(name)
+    }
+}
+
+/// Saves daemon log entries to a log file from a background thread, so
+/// slow disks and rotation never hold up the dashboard.
+///
+/// Dropping the writer writes out the entries still queued.
+pub struct LogFileWriter {
+    tx: Option<std_mpsc::Sender<daemon::LogEntry>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LogFileWriter {
+    pub fn spawn(options: LogFileOptions, events: mpsc::UnboundedSender<Event>) -> Self {
+        let (tx, rx) = std_mpsc::channel();
+        let thread = thread::spawn(move || run(LogFile::new(options), rx, events));
+        Self {
+            tx: Some(tx),
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue an entry to be appended to the file
+    pub fn write(&self, entry: &daemon::LogEntry) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(entry.clone());
+        }
+    }
+}
+
+impl Drop for LogFileWriter {
+    fn drop(&mut self) {
+        // Closing the channel lets the thread finish once the queue is empty
+        self.tx = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Append queued entries, flushing whenever the queue runs dry
+fn run(
+    mut file: LogFile,
+    rx: std_mpsc::Receiver<daemon::LogEntry>,
+    events: mpsc::UnboundedSender<Event>,
+) {
+    // Report a failure once, not for every entry while it persists
+    let mut failing = false;
+    while let Ok(entry) = rx.recv() {
+        let mut result = file.write(&entry.into());
+        for entry in rx.try_iter() {
+            if result.is_ok() {
+                result = file.write(&entry.into());
+            }
+        }
+        match result.and_then(|()| file.flush()) {
+            Ok(()) => failing = false,
+            Err(e) if !failing => {
+                failing = true;
+                let _ = events.send(Event::LogFileFailed(format!(
+                    "{}: {}",
+                    file.options.path.display(),
+                    e
+                )));
+            }
+            Err(_) => {}
+        }
+    }
+    let _ = file.flush();
+}
+
+/// Log file being appended to, rotated as it grows and ages
+struct LogFile {
+    options: LogFileOptions,
+    /// Open file, or `None` until it is (re)opened by the next write
+    out: Option<BufWriter<File>>,
+    /// Bytes in the file
+    size: u64,
+    /// When the file was started, which its age is counted from
+    started: SystemTime,
+}
+
+impl LogFile {
+    fn new(options: LogFileOptions) -> Self {
+        Self {
+            options,
+            out: None,
+            size: 0,
+            started: SystemTime::now(),
+        }
+    }
+
+    fn write(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let mut line = log_export::to_json(entry).to_string();
+        line.push('\n');
+        if self.out.is_none() {
+            self.open()?;
+        }
+        if self.due_for_rotation(line.len() as u64) {
+            self.rotate()?;
+        }
+        if let Some(out) = &mut self.out {
+            out.write_all(line.as_bytes())?;
+            self.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.out {
+            Some(out) => out.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Open the file for appending, picking up its size and age if it exists
+    fn open(&mut self) -> io::Result<()> {
+        let path = &self.options.path;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        self.size = metadata.len();
+        // Not every filesystem records creation times
+        self.started = metadata
+            .created()
+            .ok()
+            .filter(|_| self.size > 0)
+            .unwrap_or_else(SystemTime::now);
+        self.out = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    /// Whether appending `incoming` bytes should start a new file first
+    fn due_for_rotation(&self, incoming: u64) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        let too_big = self
+            .options
+            .max_size
+            .is_some_and(|max| self.size + incoming > max);
+        let too_old = self
+            .options
+            .max_age
+            .is_some_and(|max| self.started.elapsed().is_ok_and(|age| age >= max));
+        too_big || too_old
+    }
+
+    /// Move the file aside, shifting older rotated files up and dropping
+    /// those beyond the number kept, and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        // Close the file first; Windows cannot move or delete open files
+        if let Some(mut out) = self.out.take() {
+            out.flush()?;
+        }
+        let options = &self.options;
+        if options.keep == 0 {
+            fs::remove_file(&options.path)?;
+        } else {
+            remove_if_exists(&options.rotated(options.keep))?;
+            for n in (1..options.keep).rev() {
+                let from = options.rotated(n);
+                if from.exists() {
+                    fs::rename(&from, options.rotated(n + 1))?;
+                }
+            }
+            if options.compress {
+                compress(&options.path, &options.rotated(1))?;
+                fs::remove_file(&options.path)?;
+            } else {
+                fs::rename(&options.path, options.rotated(1))?;
+            }
+        }
+        self.open()
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Write a gzip-compressed copy of `from` to `to`
+fn compress(from: &Path, to: &Path) -> io::Result<()> {
+    let mut input = File::open(from)?;
+    let mut output = GzEncoder::new(BufWriter::new(File::create(to)?), Compression::default());
+    io::copy(&mut input, &mut output)?;
+    output.finish()?.flush()
+}
+
+/// Read every entry in a log file, decompressing it if it ends in `.gz`.
+///
+/// Lines that do not parse, such as one cut short by a crash, are skipped.
+pub fn read_entries(path: &Path) -> io::Result<Vec<LogEntry>> {
+    let file = File::open(path)?;
+    let input: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(input).lines() {
+        if let Some(entry) = log_export::from_json(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> LogEntry {
+        daemon::LogEntry {
+            timestamp: 1_700_000_000_000,
+            level: daemon::LogLevel::Info.into(),
+            message: message.to_string(),
+            source: "test".to_string(),
+        }
+        .into()
+    }
+
+    /// Bytes an entry takes up in the file
+    fn line_len(message: &str) -> u64 {
+        log_export::to_json(&entry(message)).to_string().len() as u64 + 1
+    }
+
+    fn messages(path: &Path) -> Vec<String> {
+        read_entries(path)
+            .expect("read log file")
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect()
+    }
+
+    #[test]
+    fn rotated_file_names() {
+        let mut options = LogFileOptions::new(PathBuf::from("logs/daemon.jsonl"));
+        assert_eq!(options.rotated(1), PathBuf::from("logs/daemon.jsonl.1.gz"));
+        options.compress = false;
+        assert_eq!(options.rotated(3), PathBuf::from("logs/daemon.jsonl.3"));
+    }
+
+    #[test]
+    fn rotates_past_the_size_limit() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let options = LogFileOptions {
+            max_size: Some(2 * line_len("one")),
+            ..LogFileOptions::new(dir.path().join("daemon.jsonl"))
+        };
+        let mut file = LogFile::new(options.clone());
+
+        // Filling the file exactly to the limit keeps it
+        file.write(&entry("one")).expect("write");
+        file.write(&entry("two")).expect("write");
+        file.flush().expect("flush");
+        assert!(!options.rotated(1).exists());
+
+        file.write(&entry("six")).expect("write");
+        file.flush().expect("flush");
+        assert_eq!(messages(&options.rotated(1)), ["one", "two"]);
+        assert_eq!(messages(&options.path), ["six"]);
+        assert!(!dir.path().join("daemon.jsonl.1").exists());
+    }
+
+    #[test]
+    fn rotated_files_shift_and_expire() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let options = LogFileOptions {
+            max_size: Some(line_len("one")),
+            keep: 2,
+            compress: false,
+            ..LogFileOptions::new(dir.path().join("daemon.jsonl"))
+        };
+        let mut file = LogFile::new(options.clone());
+        for message in ["one", "two", "six", "ten"] {
+            file.write(&entry(message)).expect("write");
+        }
+        file.flush().expect("flush");
+
+        assert_eq!(messages(&options.path), ["ten"]);
+        assert_eq!(messages(&options.rotated(1)), ["six"]);
+        assert_eq!(messages(&options.rotated(2)), ["two"]);
+        assert!(!options.rotated(3).exists());
+    }
+
+    #[test]
+    fn appends_to_an_existing_file() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let options = LogFileOptions {
+            max_size: Some(2 * line_len("one")),
+            ..LogFileOptions::new(dir.path().join("daemon.jsonl"))
+        };
+        let mut file = LogFile::new(options.clone());
+        file.write(&entry("one")).expect("write");
+        file.flush().expect("flush");
+        drop(file);
+
+        // The size left by the previous run counts towards the limit
+        let mut file = LogFile::new(options.clone());
+        file.write(&entry("two")).expect("write");
+        file.write(&entry("six")).expect("write");
+        file.flush().expect("flush");
+        assert_eq!(messages(&options.rotated(1)), ["one", "two"]);
+        assert_eq!(messages(&options.path), ["six"]);
+    }
+}
@@ -0,0 +1,353 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::app::LogEntry;
+use daemon_controller::grpc::daemon::LogLevel;
+
+/// Order of entries in the logs panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogOrder {
+    /// Oldest first, as received
+    #[default]
+    Time,
+    /// Most severe first, oldest first within a level
+    Level,
+}
+
+/// A compiled search or filter query.
+///
+/// Matching is case-insensitive unless the query contains an uppercase
+/// letter.
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub text: String,
+    pub regex: bool,
+    pattern: Regex,
+}
+
+impl Query {
+    pub fn new(text: &str, regex: bool) -> Result<Self, String> {
+        let source = if regex {
+            text.to_string()
+        } else {
+            regex::escape(text)
+        };
+        let pattern = RegexBuilder::new(&source)
+            .case_insensitive(!text.chars().any(char::is_uppercase))
+            .build()
+            .map_err(|e| match e {
+                regex::Error::Syntax(_) => "invalid regex".to_string(),
+                e => e.to_string(),
+            })?;
+        Ok(Self {
+            text: text.to_string(),
+            regex,
+            pattern,
+        })
+    }
+
+    /// Whether the entry's source or message matches
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.pattern.is_match(&entry.message) || self.pattern.is_match(&entry.source)
+    }
+
+    /// Byte ranges of the non-empty matches in `text`
+    pub fn find_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    /// Query as shown in the panel title, with slashes around regexes
+    pub fn display(&self) -> String {
+        if self.regex {
+            format!("/{}/", self.text)
+        } else {
+            format!("\"{}\"", self.text)
+        }
+    }
+}
+
+/// What a query being typed will be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputTarget {
+    /// Highlight matches and jump between them
+    Search,
+    /// Hide entries that do not match
+    Filter,
+}
+
+/// Query being typed in the logs panel
+#[derive(Debug, Clone)]
+pub struct LogInput {
+    pub target: InputTarget,
+    pub text: String,
+    pub regex: bool,
+    /// Why the text does not compile, if it does not
+    pub error: Option<String>,
+    /// Query in effect before typing started, restored on cancel
+    previous: Option<Query>,
+}
+
+/// Search, filter and level settings of the logs panel
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Least severe level shown, or `None` to show every level
+    pub min_level: Option<LogLevel>,
+    /// Only entries from this source are shown, if set
+    pub source: Option<String>,
+    /// Entries not matching this are hidden
+    pub filter: Option<Query>,
+    /// Matches of this are highlighted
+    pub search: Option<Query>,
+    pub order: LogOrder,
+    /// Query being typed, if any
+    pub input: Option<LogInput>,
+}
+
+impl LogFilter {
+    /// Whether an entry passes the level, source and filter
+    pub fn shows(&self, entry: &LogEntry) -> bool {
+        self.min_level.is_none_or(|min| entry.level >= min)
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|source| entry.source == *source)
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|query| query.matches(entry))
+    }
+
+    /// Whether entries are being hidden
+    pub fn is_active(&self) -> bool {
+        self.min_level.is_some() || self.source.is_some() || self.filter.is_some()
+    }
+
+    /// Show only more severe levels, wrapping around to every level
+    pub fn cycle_min_level(&mut self) {
+        self.min_level = match self.min_level {
+            // Debug and up is every level
+            None => Some(LogLevel::Info),
+            Some(level) => level.next(),
+        };
+    }
+
+    /// Switch between time and level order
+    pub fn toggle_order(&mut self) {
+        self.order = match self.order {
+            LogOrder::Time => LogOrder::Level,
+            LogOrder::Level => LogOrder::Time,
+        };
+    }
+
+    /// Show only the next of `sources` after the current one, wrapping
+    /// around to every source
+    pub fn cycle_source(&mut self, sources: &[&str]) {
+        let next = match &self.source {
+            None => 0,
+            Some(current) => sources
+                .iter()
+                .position(|s| s == current)
+                .map_or(0, |i| i + 1),
+        };
+        self.source = sources.get(next).map(|s| s.to_string());
+    }
+
+    /// Start typing a query, beginning with the one in effect
+    pub fn start_input(&mut self, target: InputTarget) {
+        let previous = self.query(target).clone();
+        self.input = Some(LogInput {
+            target,
+            text: previous
+                .as_ref()
+                .map(|q| q.text.clone())
+                .unwrap_or_default(),
+            regex: previous.as_ref().is_some_and(|q| q.regex),
+            error: None,
+            previous,
+        });
+    }
+
+    /// Apply the text typed so far, so results update while typing
+    pub fn update_input(&mut self) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+        let query = if input.text.is_empty() {
+            Ok(None)
+        } else {
+            Query::new(&input.text, input.regex).map(Some)
+        };
+        let target = input.target;
+        match query {
+            Ok(query) => {
+                input.error = None;
+                *self.query_mut(target) = query;
+            }
+            // Keep the last valid query while a regex is half typed
+            Err(e) => input.error = Some(e),
+        }
+    }
+
+    /// Stop typing, keeping the query
+    pub fn accept_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Stop typing, restoring the query in effect before
+    pub fn cancel_input(&mut self) {
+        if let Some(input) = self.input.take() {
+            *self.query_mut(input.target) = input.previous;
+        }
+    }
+
+    fn query(&self, target: InputTarget) -> &Option<Query> {
+        match target {
+            InputTarget::Search => &self.search,
+            InputTarget::Filter => &self.filter,
+        }
+    }
+
+    fn query_mut(&mut self, target: InputTarget) -> &mut Option<Query> {
+        match target {
+            InputTarget::Search => &mut self.search,
+            InputTarget::Filter => &mut self.filter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: LogLevel, source: &str, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: None,
+            level,
+            origin: Default::default(),
+            message: message.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    fn query(text: &str, regex: bool) -> Query {
+        Query::new(text, regex).expect("valid query")
+    }
+
+    #[test]
+    fn smart_case() {
+        let line = entry(LogLevel::Info, "http", "Request Completed");
+        assert!(query("request", false).matches(&line));
+        assert!(query("Request", false).matches(&line));
+        assert!(!query("completeD", false).matches(&line));
+        assert!(query("HTTP", false).matches(&entry(LogLevel::Info, "HTTP", "")));
+        assert!(!query("HTTP", false).matches(&line));
+    }
+
+    #[test]
+    fn text_and_regex_queries() {
+        let line = entry(LogLevel::Info, "db", "query took 12ms (slow)");
+        assert!(query("(slow)", false).matches(&line));
+        assert!(!query("took .*ms", false).matches(&line));
+        assert!(query("took .*ms", true).matches(&line));
+        assert!(query("^db$", true).matches(&line));
+        assert_eq!(query("o", false).find_ranges("foo bar"), [1..2, 2..3]);
+        // Empty matches are not highlighted
+        assert_eq!(query("x*", true).find_ranges("axxbx"), [1..3, 4..5]);
+        assert_eq!(query("slow", false).display(), "\"slow\"");
+        assert_eq!(query("sl.w", true).display(), "/sl.w/");
+    }
+
+    #[test]
+    fn invalid_regex() {
+        assert_eq!(
+            Query::new("(unclosed", true).err(),
+            Some("invalid regex".to_string())
+        );
+        assert!(Query::new("(unclosed", false).is_ok());
+    }
+
+    #[test]
+    fn half_typed_regex_keeps_the_last_valid_query() {
+        let mut filter = LogFilter::default();
+        filter.start_input(InputTarget::Filter);
+        let input = filter.input.as_mut().expect("typing");
+        input.regex = true;
+        input.text = "err".to_string();
+        filter.update_input();
+        filter.input.as_mut().expect("typing").text.push('(');
+        filter.update_input();
+        assert_eq!(
+            filter.input.as_ref().and_then(|i| i.error.clone()),
+            Some("invalid regex".to_string())
+        );
+        assert_eq!(
+            filter.filter.as_ref().map(|q| q.display()),
+            Some("/err/".to_string())
+        );
+
+        filter.cancel_input();
+        assert!(filter.filter.is_none());
+    }
+
+    #[test]
+    fn min_level_cycles_back_to_every_level() {
+        let mut filter = LogFilter::default();
+        let debug = entry(LogLevel::Debug, "", "");
+        let warn = entry(LogLevel::Warn, "", "");
+        assert!(!filter.is_active());
+        assert!(filter.shows(&debug));
+
+        let mut levels = Vec::new();
+        for _ in 0..5 {
+            filter.cycle_min_level();
+            levels.push(filter.min_level);
+        }
+        assert_eq!(
+            levels,
+            [
+                Some(LogLevel::Info),
+                Some(LogLevel::Warn),
+                Some(LogLevel::Error),
+                None,
+                Some(LogLevel::Info),
+            ]
+        );
+
+        filter.min_level = Some(LogLevel::Warn);
+        assert!(filter.is_active());
+        assert!(!filter.shows(&debug));
+        assert!(filter.shows(&warn));
+        assert!(filter.shows(&entry(LogLevel::Error, "", "")));
+    }
+
+    #[test]
+    fn source_filter() {
+        let mut filter = LogFilter::default();
+        let sources = ["db", "http"];
+        let db = entry(LogLevel::Info, "db", "connected");
+        let http = entry(LogLevel::Info, "http", "connected");
+
+        filter.cycle_source(&sources);
+        assert_eq!(filter.source.as_deref(), Some("db"));
+        assert!(filter.shows(&db));
+        assert!(!filter.shows(&http));
+        filter.cycle_source(&sources);
+        assert_eq!(filter.source.as_deref(), Some("http"));
+        filter.cycle_source(&sources);
+        assert_eq!(filter.source, None);
+
+        // A source that has gone away starts the cycle again
+        filter.source = Some("cache".to_string());
+        filter.cycle_source(&sources);
+        assert_eq!(filter.source.as_deref(), Some("db"));
+
+        // The source and text filter both apply
+        filter.filter = Some(query("disconnected", false));
+        assert!(!filter.shows(&db));
+    }
+}
@@ -1,54 +1,148 @@
+mod alerts;
 mod app;
+mod audit;
+mod bulk;
+mod cli;
+mod config;
 mod events;
-mod grpc;
+mod fleet;
+mod keymap;
+mod log_buffer;
+mod log_export;
+mod log_fields;
+mod log_file;
+mod log_filter;
+mod metrics;
 mod ui;
+mod worker;
 
-use std::{io, time::Duration};
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+    process::ExitCode,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
-use app::{App, ConnectionStatus};
+use alerts::{AlertEngine, AlertRule, DEFAULT_RULES};
+use app::{
+    App, Confirmation, ConnectionStatus, ControlAction, PendingConfirm, TextScroll,
+    TransportSecurity, View,
+};
+use audit::{Auditor, Origin};
+use bulk::{BulkMenu, BulkMode, BulkOperation, BulkRunner};
+use clap::Parser;
+use cli::{AlertNotify, Cli};
 use color_eyre::Result;
+use config::{FleetSettings, Settings};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use events::{Event, EventHandler};
-use grpc::DaemonClient;
+use daemon_controller::grpc::{daemon::LogLevel, DaemonClient};
+use events::{session_sender, CallError, Event, EventHandler};
+use fleet::{Fleet, FleetPoller};
+use keymap::{Action, Context, Key};
+use log_export::ExportOptions;
+use log_file::LogFileWriter;
+use log_filter::InputTarget;
 use ratatui::{backend::CrosstermBackend, Terminal};
+use tokio::sync::mpsc;
 use ui::render_dashboard;
+use worker::{ClientWorker, Command};
 
 /// Tick rate for UI refresh (in milliseconds)
 const TICK_RATE_MS: u64 = 250;
 
-/// Default daemon address
-const DEFAULT_DAEMON_ADDRESS: &str = "http://127.0.0.1:50051";
+/// Rows Page Up and Page Down scroll the log detail popup and the help by
+const POPUP_PAGE_ROWS: usize = 10;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     // Initialize error handling
     color_eyre::install()?;
 
-    // Parse command-line args (simple version)
-    let daemon_address = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| DEFAULT_DAEMON_ADDRESS.to_string());
+    // Parse command-line args and merge them with the config file
+    let cli = Cli::parse();
+    if let Some(path) = &cli.view_logs {
+        if cli.command.is_some() {
+            eprintln!("Error: --view-logs only applies to the dashboard");
+            return Ok(ExitCode::from(cli::EXIT_USAGE));
+        }
+        return view_logs(path.clone(), &cli).await;
+    }
+    let settings = match Settings::load(&cli) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return Ok(ExitCode::from(cli::EXIT_USAGE));
+        }
+    };
+
+    // Run a headless subcommand if one was given
+    if let Some(command) = cli.command {
+        return Ok(cli::run(command, settings, cli.output).await);
+    }
 
     // Setup terminal
     let mut terminal = setup_terminal()?;
 
     // Create app and run
-    let mut app = App::new(daemon_address.clone());
-    let mut client = DaemonClient::new(daemon_address);
-
-    app.add_log("INFO", "Daemon Controller started".to_string());
-    app.add_log("INFO", format!("Target: {}", app.daemon_address));
+    let mut app = App::new(settings.address.clone());
+    app.keymap = settings.keymap.clone();
+    app.log_export = ExportOptions {
+        path: cli.export_logs.clone(),
+        format: cli.export_format,
+    };
+    app.add_log(LogLevel::Info, "Daemon Controller started".to_string());
 
-    let result = run_app(&mut terminal, &mut app, &mut client).await;
+    let result = run_app(&mut terminal, &mut app, &settings, cli.alert_notify).await;
 
     // Restore terminal
     restore_terminal(&mut terminal)?;
 
-    result
+    if cli.export_logs.is_some() {
+        let (path, format) = app.log_export.target();
+        match log_export::export(app.logs.iter(), &path, format) {
+            Ok(count) => eprintln!("Exported {} log lines to {}", count, path.display()),
+            Err(e) => eprintln!("Failed to export logs to {}: {}", path.display(), e),
+        }
+    }
+
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+/// Browse a saved log file without connecting to a daemon
+async fn view_logs(path: std::path::PathBuf, cli: &Cli) -> Result<ExitCode> {
+    let keymap = match config::load_keymap(cli) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return Ok(ExitCode::from(cli::EXIT_USAGE));
+        }
+    };
+    let entries = match log_file::read_entries(&path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", path.display(), e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    let mut terminal = setup_terminal()?;
+    let mut app = App {
+        keymap,
+        log_export: ExportOptions {
+            path: cli.export_logs.clone(),
+            format: cli.export_format,
+        },
+        ..Default::default()
+    };
+    app.view_log_file(path, entries);
+    let result = run_viewer(&mut terminal, &mut app).await;
+    restore_terminal(&mut terminal)?;
+    result.map(|()| ExitCode::SUCCESS)
 }
 
 /// Setup terminal for TUI
@@ -73,13 +167,124 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Re
     Ok(())
 }
 
+/// Id of the next dashboard session
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// Connection to the daemon shown in the dashboard
+struct Session {
+    /// Tags the events posted by this session's tasks
+    id: u64,
+    worker: ClientWorker,
+    /// Saves streamed daemon logs, if configured
+    log_file: Option<LogFileWriter>,
+    poll_interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+/// Point the dashboard at a daemon and start a client worker for it
+fn open_session(
+    app: &mut App,
+    settings: &Settings,
+    events: mpsc::UnboundedSender<Event>,
+) -> Session {
+    app.profile = settings.profile.clone();
+    app.security = TransportSecurity::of(settings);
+    let rules = settings.alerts.clone().unwrap_or_else(|| {
+        DEFAULT_RULES
+            .iter()
+            .map(|rule| rule.parse())
+            .collect::<Result<Vec<AlertRule>, _>>()
+            .expect("default alert rules are valid")
+    });
+    app.alerts = AlertEngine::new(rules);
+    app.confirm_policy = settings.confirm;
+    app.audit_file = settings.audit_file.clone();
+    app.logs.set_capacity(settings.log_buffer_lines);
+
+    if let Some(profile) = &app.profile {
+        app.add_log(LogLevel::Info, format!("Profile: {}", profile));
+    }
+    app.add_log(LogLevel::Info, format!("Target: {}", app.daemon_address));
+    if app.security == TransportSecurity::TlsUnverified {
+        app.add_log(
+            LogLevel::Warn,
+            "TLS certificate verification is disabled".to_string(),
+        );
+    }
+
+    if let Some(log_file) = &settings.log_file {
+        app.add_log(
+            LogLevel::Info,
+            format!("Saving daemon logs to {}", log_file.path.display()),
+        );
+    }
+
+    let id = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+    let events = session_sender(id, events);
+    let client = DaemonClient::with_options(settings.address.clone(), settings.client.clone());
+    Session {
+        id,
+        log_file: settings
+            .log_file
+            .clone()
+            .map(|options| LogFileWriter::spawn(options, events.clone())),
+        worker: ClientWorker::spawn(
+            client,
+            settings.log_min_level,
+            settings.tail_lines,
+            Auditor::new(settings, Origin::Dashboard),
+            events,
+        ),
+        poll_interval: settings.poll_interval,
+        last_poll: None,
+    }
+}
+
+/// Open the dashboard for a fleet member and connect to it
+fn open_fleet_member(
+    app: &mut App,
+    fleet: &FleetSettings,
+    index: usize,
+    events: mpsc::UnboundedSender<Event>,
+) -> Option<Session> {
+    let settings = fleet.members.get(index)?;
+    app.switch_daemon(settings.address.clone());
+    let session = open_session(app, settings, events);
+    connect_to_daemon(app, &session.worker);
+    Some(session)
+}
+
 /// Main application loop
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    client: &mut DaemonClient,
+    settings: &Settings,
+    alert_notify: AlertNotify,
 ) -> Result<()> {
-    let mut events = EventHandler::new(Duration::from_millis(TICK_RATE_MS));
+    // Tick at least as often as any daemon is polled
+    let tick_rate = settings
+        .fleet
+        .iter()
+        .flat_map(|fleet| &fleet.members)
+        .map(|member| member.poll_interval)
+        .fold(
+            Duration::from_millis(TICK_RATE_MS).min(settings.poll_interval),
+            Duration::min,
+        );
+    let mut events = EventHandler::new(tick_rate);
+
+    // With a fleet, the dashboard opens once a daemon is picked
+    // Dropping the runner cancels the bulk action
+    let mut _bulk: Option<BulkRunner> = None;
+    let (mut session, _poller) = match &settings.fleet {
+        Some(fleet) => {
+            app.fleet = Some(Fleet::new(&fleet.members));
+            app.view = View::Fleet;
+            let poller = FleetPoller::spawn(&fleet.members, fleet.poll_interval, events.sender());
+            (None, Some(poller))
+        }
+        None => (Some(open_session(app, settings, events.sender())), None),
+    };
 
     loop {
         // Draw UI
@@ -89,12 +294,49 @@ async fn run_app(
         if let Some(event) = events.next().await {
             match event {
                 Event::Key(key) => {
-                    handle_key_event(app, client, key.code, key.modifiers).await;
+                    if let (View::Fleet, Some(fleet)) = (app.view, &settings.fleet) {
+                        match handle_fleet_key(app, fleet, key.code, key.modifiers) {
+                            Some(FleetCommand::Open(index)) => {
+                                session = open_fleet_member(app, fleet, index, events.sender());
+                            }
+                            Some(FleetCommand::RunBulk) => {
+                                _bulk = start_bulk(app, fleet, events.sender());
+                            }
+                            Some(FleetCommand::CancelBulk) => {
+                                _bulk = None;
+                                let operation =
+                                    app.fleet.as_mut().and_then(|f| f.operation.as_mut());
+                                if let Some(operation) = operation {
+                                    operation.finish();
+                                    let summary = operation.summary();
+                                    app.set_status_message(format!("Cancelled - {}", summary));
+                                }
+                            }
+                            None => {}
+                        }
+                    } else if let Some(current) = &session {
+                        handle_key_event(app, &current.worker, key.code, key.modifiers);
+                        if app.view == View::Fleet {
+                            // The fleet poller keeps watching the daemon
+                            session = None;
+                        }
+                    }
                 }
                 Event::Tick => {
-                    // Periodic update - refresh data if connected
-                    if client.is_connected() {
-                        refresh_data(app, client).await;
+                    app.tick();
+                    // Periodic update - refresh data if connected, idle and due
+                    if let Some(session) = &mut session {
+                        let due = session
+                            .last_poll
+                            .is_none_or(|at| at.elapsed() >= session.poll_interval);
+                        if app.connection_status == ConnectionStatus::Connected
+                            && !app.refreshing
+                            && due
+                        {
+                            app.refreshing = true;
+                            session.last_poll = Some(Instant::now());
+                            session.worker.send(Command::Refresh);
+                        }
                     }
                 }
                 Event::Resize(_, _) => {
@@ -103,9 +345,28 @@ async fn run_app(
                 Event::Mouse(_) => {
                     // Mouse events handled here if needed
                 }
+                Event::Session { session: id, event } => {
+                    // Events still queued from an earlier session describe
+                    // another daemon
+                    if let Some(current) = session.as_ref().filter(|s| s.id == id) {
+                        if let (Event::DaemonLog(entry), Some(log_file)) =
+                            (event.as_ref(), &current.log_file)
+                        {
+                            log_file.write(entry);
+                        }
+                        handle_worker_event(app, *event);
+                    }
+                }
+                event => handle_worker_event(app, event),
             }
         }
 
+        // Signal newly fired alerts
+        for message in app.alerts.take_notifications() {
+            app.add_log(LogLevel::Warn, message.clone());
+            notify(terminal.backend_mut(), alert_notify, &message)?;
+        }
+
         // Check if we should quit
         if app.should_quit {
             break;
@@ -115,144 +376,684 @@ async fn run_app(
     Ok(())
 }
 
-/// Handle keyboard input
-async fn handle_key_event(
+/// Event loop of the read-only log file viewer
+async fn run_viewer(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    client: &mut DaemonClient,
-    code: KeyCode,
-    modifiers: KeyModifiers,
-) {
-    // Global keybindings
-    match code {
-        KeyCode::Char('q') | KeyCode::Char('Q') => {
-            app.quit();
-            return;
+) -> Result<()> {
+    let mut events = EventHandler::new(Duration::from_millis(TICK_RATE_MS));
+    while !app.should_quit {
+        terminal.draw(|frame| render_dashboard(frame, app))?;
+        match events.next().await {
+            Some(Event::Key(key)) => handle_viewer_key(app, key.code, key.modifiers),
+            Some(Event::Tick) => app.tick(),
+            Some(_) => {}
+            None => break,
         }
-        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.quit();
-            return;
+    }
+    Ok(())
+}
+
+/// Ring the bell and/or send a desktop notification for an alert
+fn notify(out: &mut impl Write, mode: AlertNotify, message: &str) -> io::Result<()> {
+    if matches!(mode, AlertNotify::Bell | AlertNotify::Both) {
+        out.write_all(b"\x07")?;
+    }
+    if matches!(mode, AlertNotify::Osc9 | AlertNotify::Both) {
+        // Strip control characters that would terminate the sequence early
+        let message: String = message.chars().filter(|c| !c.is_control()).collect();
+        write!(out, "\x1b]9;{}\x07", message)?;
+    }
+    out.flush()
+}
+
+/// Handle keyboard input
+fn handle_key_event(app: &mut App, worker: &ClientWorker, code: KeyCode, modifiers: KeyModifiers) {
+    // Any key dismisses a status message and brings back the key hints
+    app.clear_status_message();
+
+    // The confirmation popup takes all input until it is answered
+    if app.confirm.is_some() {
+        handle_confirm_key(app, worker, code, modifiers);
+        return;
+    }
+    if app.help.is_some() {
+        handle_help_key(app, code, modifiers);
+        return;
+    }
+    if app.log_detail.is_some() {
+        handle_log_detail_key(app, code, modifiers);
+        return;
+    }
+    if app.log_filter.input.is_some() {
+        handle_log_input(app, code, modifiers);
+        return;
+    }
+
+    // Global keys come first, then those of the view or focused panel
+    let local = match app.view {
+        View::MetricsHistory => Context::MetricsHistory,
+        View::Audit => Context::Audit,
+        View::Dashboard | View::Fleet | View::LogFile => match app.focused_panel {
+            app::FocusedPanel::Status => Context::StatusPanel,
+            app::FocusedPanel::Controls => Context::ControlsPanel,
+            app::FocusedPanel::Logs => Context::LogsPanel,
+        },
+    };
+    let key = Key::pressed(code, modifiers);
+    let Some((context, action)) = app.press_key(&[Context::Global, local], key) else {
+        return;
+    };
+    match (context, action) {
+        (Context::Global, Action::Quit) => app.quit(),
+        (Context::Global, Action::Help) => app.open_help(),
+        (Context::Global, Action::NextPanel) => app.focus_next(),
+        (Context::Global, Action::PrevPanel) => app.focus_prev(),
+        (Context::Global, Action::Connect) => connect_to_daemon(app, worker),
+        (Context::Global, Action::Disconnect) => disconnect_from_daemon(app, worker),
+        (Context::Global, Action::MetricsHistory) => app.toggle_metrics_history(),
+        (Context::Global, Action::Audit) => app.toggle_audit(),
+        // The main loop closes the session once the fleet view is shown
+        (Context::Global, Action::Fleet) => {
+            app.show_fleet();
         }
-        KeyCode::Tab => {
-            app.focus_next();
-            return;
+        (Context::Global, Action::Start) => execute_action(app, worker, ControlAction::Start),
+        (Context::Global, Action::Stop) => execute_action(app, worker, ControlAction::Stop),
+        (Context::Global, Action::Restart) => execute_action(app, worker, ControlAction::Restart),
+        (Context::Global, Action::Reload) => execute_action(app, worker, ControlAction::Reload),
+        (Context::MetricsHistory, Action::HistoryWindow) => app.next_history_window(),
+        (Context::Audit, Action::Up) => app.select_prev_audit(),
+        (Context::Audit, Action::Down) => app.select_next_audit(),
+        (Context::ControlsPanel, Action::Up) => app.select_prev_action(),
+        (Context::ControlsPanel, Action::Down) => app.select_next_action(),
+        (Context::ControlsPanel, Action::Execute) => {
+            let action = app.current_action();
+            execute_action(app, worker, action);
         }
-        KeyCode::BackTab => {
-            app.focus_prev();
-            return;
+        (Context::LogsPanel, action) => handle_logs_action(app, action),
+        _ => {}
+    }
+}
+
+/// Handle keyboard input in the read-only log file viewer
+fn handle_viewer_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    app.clear_status_message();
+    if app.help.is_some() {
+        handle_help_key(app, code, modifiers);
+        return;
+    }
+    if app.log_detail.is_some() {
+        handle_log_detail_key(app, code, modifiers);
+        return;
+    }
+    if app.log_filter.input.is_some() {
+        handle_log_input(app, code, modifiers);
+        return;
+    }
+    // The viewer has no daemon, so only quitting and help apply globally
+    let key = Key::pressed(code, modifiers);
+    match app.press_key(&[Context::Global, Context::LogsPanel], key) {
+        Some((Context::Global, Action::Quit)) => app.quit(),
+        Some((Context::Global, Action::Help)) => app.open_help(),
+        Some((Context::LogsPanel, action)) => handle_logs_action(app, action),
+        _ => {}
+    }
+}
+
+/// Run an action of the logs panel
+fn handle_logs_action(app: &mut App, action: Action) {
+    match action {
+        Action::Up => app.scroll_logs_up(),
+        Action::Down => app.scroll_logs_down(),
+        Action::PageUp => app.page_logs_up(),
+        Action::PageDown => app.page_logs_down(),
+        Action::Top => app.scroll_logs_to_start(),
+        Action::Bottom => app.scroll_logs_to_end(),
+        Action::Search => app.log_filter.start_input(InputTarget::Search),
+        Action::Filter => app.log_filter.start_input(InputTarget::Filter),
+        Action::Level => {
+            app.log_filter.cycle_min_level();
+            app.scroll_logs_to_end();
         }
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            connect_to_daemon(app, client).await;
-            return;
+        Action::Source => app.cycle_source_filter(),
+        Action::Order => {
+            app.log_filter.toggle_order();
+            app.scroll_logs_to_end();
         }
-        KeyCode::Char('d') | KeyCode::Char('D') => {
-            disconnect_from_daemon(app, client);
+        Action::Details => app.open_log_detail(),
+        Action::ExportShown => app.export_logs(false),
+        Action::ExportAll => app.export_logs(true),
+        Action::NextMatch => next_match(app, true),
+        Action::PrevMatch => next_match(app, false),
+        _ => {}
+    }
+}
+
+/// Handle keyboard input while a search or filter query is being typed
+fn handle_log_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let key = Key::pressed(code, modifiers);
+    // Characters are typed into the query, so only other keys are looked up
+    let typed = key.typed_char();
+    let action = match typed {
+        Some(_) => None,
+        None => app.press_key(&[Context::Input], key),
+    };
+    let Some(input) = &mut app.log_filter.input else {
+        return;
+    };
+    let target = input.target;
+    match (action, typed) {
+        (Some((_, Action::Quit)), _) => {
+            app.quit();
             return;
         }
+        (Some((_, Action::Cancel)), _) => app.log_filter.cancel_input(),
+        (Some((_, Action::Confirm)), _) => app.log_filter.accept_input(),
+        (Some((_, Action::ToggleRegex)), _) => input.regex = !input.regex,
+        (Some((_, Action::DeleteChar)), _) => {
+            input.text.pop();
+        }
+        (None, Some(c)) => input.text.push(c),
+        _ => return,
+    }
+    app.log_filter.update_input();
+
+    // Follow the query as it is typed
+    match target {
+        InputTarget::Search => {
+            app.jump_to_match(true, true);
+        }
+        InputTarget::Filter => app.scroll_logs_to_end(),
+    }
+}
+
+/// Handle keyboard input while the log detail popup is open
+fn handle_log_detail_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let key = Key::pressed(code, modifiers);
+    let action = app.press_key(&[Context::LogDetail], key);
+    let Some(detail) = &mut app.log_detail else {
+        return;
+    };
+    match action {
+        Some((_, Action::Quit)) => app.quit(),
+        Some((_, Action::Close)) => app.log_detail = None,
+        Some((_, action)) => scroll_popup(&mut detail.scroll, action),
+        None => {}
+    }
+}
+
+/// Handle keyboard input while the help overlay is open
+fn handle_help_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let key = Key::pressed(code, modifiers);
+    let action = app.press_key(&[Context::Help], key);
+    let Some(help) = &mut app.help else {
+        return;
+    };
+    match action {
+        Some((_, Action::Quit)) => app.quit(),
+        Some((_, Action::Close)) => app.help = None,
+        Some((_, action)) => scroll_popup(help, action),
+        None => {}
+    }
+}
+
+/// Scroll the text of a popup or overlay
+fn scroll_popup(scroll: &mut TextScroll, action: Action) {
+    match action {
+        Action::Up => scroll.scroll_by(-1),
+        Action::Down => scroll.scroll_by(1),
+        Action::PageUp => scroll.scroll_by(-(POPUP_PAGE_ROWS as isize)),
+        Action::PageDown => scroll.scroll_by(POPUP_PAGE_ROWS as isize),
+        Action::Top => scroll.scroll_by(isize::MIN),
+        Action::Bottom => scroll.scroll_by(isize::MAX),
         _ => {}
     }
+}
 
-    // Panel-specific keybindings
-    match app.focused_panel {
-        app::FocusedPanel::Controls => match code {
-            KeyCode::Up | KeyCode::Char('k') => app.select_prev_action(),
-            KeyCode::Down | KeyCode::Char('j') => app.select_next_action(),
-            KeyCode::Enter => {
-                execute_action(app, client).await;
+/// Scroll to the next or previous search match
+fn next_match(app: &mut App, forward: bool) {
+    if app.log_filter.search.is_none() {
+        let message = match app.keymap.hint(Context::LogsPanel, &[Action::Search]) {
+            Some(key) => format!("No search - press {} to search the logs", key),
+            None => "No search".to_string(),
+        };
+        app.set_status_message(message);
+    } else if !app.jump_to_match(forward, false) {
+        app.set_status_message("No matches".to_string());
+    }
+}
+
+/// Requests from the fleet view handled by the main loop
+enum FleetCommand {
+    /// Open the dashboard for the member at this index
+    Open(usize),
+    /// Run the action chosen in the bulk menu on the marked members
+    RunBulk,
+    /// Stop the running bulk action
+    CancelBulk,
+}
+
+/// Handle keyboard input in the fleet view
+fn handle_fleet_key(
+    app: &mut App,
+    settings: &FleetSettings,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> Option<FleetCommand> {
+    app.clear_status_message();
+    if app.confirm.is_some() {
+        let (action, confirmed) = confirm_answer(app, code, modifiers)?;
+        if confirmed {
+            return Some(FleetCommand::RunBulk);
+        }
+        app.set_status_message(format!("Bulk {} cancelled", action.label()));
+        return None;
+    }
+    if app.help.is_some() {
+        handle_help_key(app, code, modifiers);
+        return None;
+    }
+    let menu_open = app.fleet.as_ref()?.menu.is_some();
+    let context = if menu_open {
+        Context::BulkMenu
+    } else {
+        Context::Fleet
+    };
+    let (_, action) = app.press_key(&[context], Key::pressed(code, modifiers))?;
+    let mark_hint = app.keymap.hint(Context::Fleet, &[Action::Mark]);
+    let cancel_hint = app.keymap.hint(Context::Fleet, &[Action::CancelBulk]);
+    let fleet = app.fleet.as_mut()?;
+
+    if let Some(menu) = &mut fleet.menu {
+        match action {
+            Action::Up => menu.select_prev_action(),
+            Action::Down => menu.select_next_action(),
+            Action::Mode => menu.mode = menu.mode.toggle(),
+            Action::GrowBatch => menu.grow_batch(),
+            Action::ShrinkBatch => menu.shrink_batch(),
+            Action::Run => {
+                let confirm = bulk_confirm(settings, menu.action(), &fleet.marked);
+                if confirm.kind == Confirmation::None {
+                    return Some(FleetCommand::RunBulk);
+                }
+                app.confirm = Some(confirm);
             }
+            Action::Close => fleet.menu = None,
+            Action::Quit => app.quit(),
             _ => {}
-        },
-        app::FocusedPanel::Logs => match code {
-            KeyCode::Up | KeyCode::Char('k') => app.scroll_logs_up(),
-            KeyCode::Down | KeyCode::Char('j') => app.scroll_logs_down(),
-            _ => {}
-        },
-        app::FocusedPanel::Status => {
-            // Status panel has no specific actions
         }
+        return None;
+    }
+
+    match action {
+        Action::Quit => app.quit(),
+        Action::Help => app.open_help(),
+        Action::Up => fleet.select_prev(),
+        Action::Down => fleet.select_next(),
+        Action::Mark => fleet.toggle_mark(),
+        Action::MarkAll => fleet.toggle_mark_all(),
+        Action::BulkMenu => {
+            if fleet.bulk_running() {
+                let message = match cancel_hint {
+                    Some(key) => format!("A bulk action is running - {} cancels it", key),
+                    None => "A bulk action is running".to_string(),
+                };
+                app.set_status_message(message);
+            } else if fleet.marked.is_empty() {
+                let message = match mark_hint {
+                    Some(key) => format!("Mark daemons with {} first", key),
+                    None => "Mark daemons first".to_string(),
+                };
+                app.set_status_message(message);
+            } else {
+                fleet.menu = Some(BulkMenu::new(settings.batch_size));
+            }
+        }
+        Action::CancelBulk if fleet.bulk_running() => return Some(FleetCommand::CancelBulk),
+        Action::Open => return Some(FleetCommand::Open(fleet.selected)),
+        _ => {}
     }
+    None
 }
 
-/// Connect to the daemon
-async fn connect_to_daemon(app: &mut App, client: &mut DaemonClient) {
-    if client.is_connected() {
-        app.add_log("WARN", "Already connected".to_string());
-        return;
+/// Confirmation of a bulk action, as careful as the most careful policy
+/// among its targets
+fn bulk_confirm(
+    settings: &FleetSettings,
+    action: ControlAction,
+    marked: &BTreeSet<usize>,
+) -> PendingConfirm {
+    let targets: Vec<&Settings> = marked
+        .iter()
+        .filter_map(|&index| settings.members.get(index))
+        .collect();
+    PendingConfirm {
+        action,
+        kind: targets
+            .iter()
+            .map(|member| member.confirm.for_action(action))
+            .max()
+            .unwrap_or_default(),
+        targets: targets
+            .iter()
+            .map(|member| {
+                member
+                    .profile
+                    .clone()
+                    .unwrap_or_else(|| member.address.clone())
+            })
+            .collect(),
+        input: String::new(),
     }
+}
 
-    app.set_connection_status(ConnectionStatus::Connecting);
-    app.add_log("INFO", "Connecting to daemon...".to_string());
+/// Start the bulk action chosen in the menu on the marked fleet members
+fn start_bulk(
+    app: &mut App,
+    settings: &FleetSettings,
+    events: mpsc::UnboundedSender<Event>,
+) -> Option<BulkRunner> {
+    let fleet = app.fleet.as_mut()?;
+    let menu = fleet.menu.take()?;
+    let targets: Vec<(usize, Settings)> = fleet
+        .marked
+        .iter()
+        .filter_map(|&index| Some((index, settings.members.get(index)?.clone())))
+        .collect();
+    let batch_size = match menu.mode {
+        BulkMode::Parallel => targets.len(),
+        BulkMode::Rolling => menu.batch_size,
+    };
 
-    match client.connect().await {
-        Ok(()) => {
+    fleet.operation = Some(BulkOperation::new(
+        &menu,
+        targets.iter().map(|(index, _)| *index),
+    ));
+    Some(BulkRunner::spawn(
+        menu.action(),
+        targets,
+        batch_size,
+        settings.settle_timeout,
+        events,
+    ))
+}
+
+/// Apply a result posted by the client worker
+fn handle_worker_event(app: &mut App, event: Event) {
+    match event {
+        Event::Connected { reconnected } => {
             app.set_connection_status(ConnectionStatus::Connected);
-            app.add_log("INFO", "Connected successfully".to_string());
-            // Fetch initial data
-            refresh_data(app, client).await;
+            if reconnected {
+                app.add_log(LogLevel::Info, "Reconnected successfully".to_string());
+            } else {
+                app.add_log(LogLevel::Info, "Connected successfully".to_string());
+            }
         }
-        Err(e) => {
-            app.set_connection_status(ConnectionStatus::Error("Connection failed".to_string()));
-            app.add_log("ERROR", format!("Connection failed: {}", e));
+        Event::ConnectFailed { summary, error } => {
+            app.set_connection_status(ConnectionStatus::Error(summary.to_string()));
+            app.add_log(LogLevel::Error, format!("Connection failed: {}", error));
+        }
+        Event::Reconnecting {
+            attempt,
+            next_retry,
+            error,
+        } => {
+            if matches!(app.connection_status, ConnectionStatus::Reconnecting { .. }) {
+                app.add_log(LogLevel::Warn, format!("Reconnect failed: {}", error));
+            } else {
+                app.add_log(LogLevel::Warn, "Connection lost - reconnecting".to_string());
+                app.clear_daemon_data();
+            }
+            app.set_connection_status(ConnectionStatus::Reconnecting {
+                attempt,
+                next_retry,
+            });
+            app.refreshing = false;
+        }
+        Event::Disconnected => {
+            app.set_connection_status(ConnectionStatus::Disconnected);
+            app.clear_daemon_data();
+            app.refreshing = false;
+            app.add_log(LogLevel::Info, "Disconnected from daemon".to_string());
+        }
+        Event::StatusUpdated(result) => match result {
+            Ok(status) => {
+                app.auth_failure = None;
+                app.update_status(status);
+            }
+            Err(e) => refresh_failed(app, "status", e),
+        },
+        Event::MetricsUpdated(result) => {
+            app.refreshing = false;
+            match result {
+                Ok(metrics) => app.update_metrics(metrics),
+                Err(e) => refresh_failed(app, "metrics", e),
+            }
+        }
+        Event::ControlFinished(action, result) => {
+            if app.pending_action == Some(action) {
+                app.pending_action = None;
+            }
+            match result {
+                Ok(response) => {
+                    if response.success {
+                        app.add_log(LogLevel::Info, format!("Success: {}", response.message));
+                    } else {
+                        app.add_log(LogLevel::Warn, format!("Failed: {}", response.message));
+                    }
+                }
+                Err(e) => match e.auth_hint() {
+                    Some(hint) => {
+                        let message = format!("{} rejected: {}", action.label(), e);
+                        app.add_log(LogLevel::Error, message.clone());
+                        app.set_status_message(format!("{} - {}", message, hint));
+                    }
+                    None => app.add_log(LogLevel::Error, format!("Command failed: {}", e)),
+                },
+            }
+        }
+        Event::AuditFailed(error) => {
+            app.add_log(
+                LogLevel::Warn,
+                format!("Failed to write audit record: {}", error),
+            );
+        }
+        Event::LogFileFailed(error) => {
+            app.add_log(
+                LogLevel::Warn,
+                format!("Failed to save daemon logs: {}", error),
+            );
+        }
+        Event::DaemonLog(entry) => {
+            app.add_daemon_log(entry);
+        }
+        Event::LogStreamDropped(reason) => {
+            if app.connection_status == ConnectionStatus::Connected {
+                app.add_log(LogLevel::Warn, format!("{} - resubscribing", reason));
+            }
         }
+        Event::FleetPolled { index, poll } => {
+            if let Some(fleet) = &mut app.fleet {
+                fleet.apply(index, poll);
+            }
+        }
+        Event::BulkProgress { index, status } => {
+            if let Some(operation) = app.fleet.as_mut().and_then(|f| f.operation.as_mut()) {
+                operation.update(index, status);
+            }
+        }
+        Event::BulkFinished => {
+            let operation = app.fleet.as_mut().and_then(|f| f.operation.as_mut());
+            if let Some(operation) = operation.filter(|op| !op.finished) {
+                operation.finish();
+                let summary = operation.summary();
+                app.set_status_message(summary);
+            }
+        }
+        Event::Tick
+        | Event::Key(_)
+        | Event::Mouse(_)
+        | Event::Resize(_, _)
+        | Event::Session { .. } => {}
     }
 }
 
-/// Disconnect from the daemon
-fn disconnect_from_daemon(app: &mut App, client: &mut DaemonClient) {
-    if !client.is_connected() {
-        app.add_log("WARN", "Not connected".to_string());
-        return;
+/// Log a failed refresh, reporting each authentication failure only once
+/// rather than on every poll
+fn refresh_failed(app: &mut App, what: &str, error: CallError) {
+    match error.auth_hint() {
+        Some(hint) => {
+            if app.auth_failure.as_ref() != Some(&error) {
+                app.add_log(
+                    LogLevel::Error,
+                    format!("Failed to get {}: {} - {}", what, error, hint),
+                );
+            }
+            app.auth_failure = Some(error);
+        }
+        None => app.add_log(
+            LogLevel::Error,
+            format!("Failed to get {}: {}", what, error),
+        ),
     }
+}
 
-    client.disconnect();
-    app.set_connection_status(ConnectionStatus::Disconnected);
-    app.daemon_status = None;
-    app.daemon_metrics = None;
-    app.add_log("INFO", "Disconnected from daemon".to_string());
+/// Connect to the daemon
+fn connect_to_daemon(app: &mut App, worker: &ClientWorker) {
+    match app.connection_status {
+        ConnectionStatus::Connected => {
+            app.add_log(LogLevel::Warn, "Already connected".to_string());
+            return;
+        }
+        ConnectionStatus::Connecting => {
+            app.add_log(LogLevel::Warn, "Connection already in progress".to_string());
+            return;
+        }
+        _ => {}
+    }
+
+    app.set_connection_status(ConnectionStatus::Connecting);
+    app.add_log(LogLevel::Info, "Connecting to daemon...".to_string());
+    worker.send(Command::Connect);
 }
 
-/// Execute the selected control action
-async fn execute_action(app: &mut App, client: &mut DaemonClient) {
-    if !client.is_connected() {
-        app.add_log("WARN", "Not connected - press 'c' to connect".to_string());
+/// Disconnect from the daemon
+fn disconnect_from_daemon(app: &mut App, worker: &ClientWorker) {
+    if matches!(
+        app.connection_status,
+        ConnectionStatus::Disconnected | ConnectionStatus::Error(_)
+    ) {
+        app.add_log(LogLevel::Warn, "Not connected".to_string());
         return;
     }
 
-    let action = app.current_action();
-    app.add_log("INFO", format!("Executing: {}", action.label()));
+    worker.send(Command::Disconnect);
+}
 
-    match client.control(action.to_command()).await {
-        Ok(response) => {
-            if response.success {
-                app.add_log("INFO", format!("Success: {}", response.message));
-            } else {
-                app.add_log("WARN", format!("Failed: {}", response.message));
-            }
-        }
-        Err(e) => {
-            app.add_log("ERROR", format!("Command failed: {}", e));
+/// Execute a control action, asking for confirmation first if the policy
+/// requires it
+fn execute_action(app: &mut App, worker: &ClientWorker, action: ControlAction) {
+    if !ready_for_action(app) {
+        return;
+    }
+    match app.confirm_policy.for_action(action) {
+        Confirmation::None => send_action(app, worker, action),
+        kind => {
+            app.confirm = Some(PendingConfirm {
+                action,
+                kind,
+                targets: vec![app.daemon_name().to_string()],
+                input: String::new(),
+            });
         }
     }
 }
 
-/// Refresh status and metrics from daemon
-async fn refresh_data(app: &mut App, client: &mut DaemonClient) {
-    // Get status
-    match client.get_status().await {
-        Ok(status) => {
-            app.update_status(status);
-        }
-        Err(e) => {
-            app.add_log("ERROR", format!("Failed to get status: {}", e));
+/// Handle keyboard input while the confirmation popup is open
+fn handle_confirm_key(
+    app: &mut App,
+    worker: &ClientWorker,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) {
+    let Some((action, confirmed)) = confirm_answer(app, code, modifiers) else {
+        return;
+    };
+    if confirmed {
+        // The connection may have changed while the popup was open
+        if ready_for_action(app) {
+            send_action(app, worker, action);
         }
+    } else {
+        app.add_log(LogLevel::Info, format!("{} cancelled", action.label()));
     }
+}
 
-    // Get metrics
-    match client.get_metrics().await {
-        Ok(metrics) => {
-            app.update_metrics(metrics);
+/// Feed a key to the confirmation popup, closing it and returning the
+/// action and whether it was confirmed once answered
+fn confirm_answer(
+    app: &mut App,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> Option<(ControlAction, bool)> {
+    let kind = app.confirm.as_ref()?.kind;
+    let key = Key::pressed(code, modifiers);
+    // A daemon name is typed like a query; otherwise the popup has keys of
+    // its own
+    let (context, typed) = match kind {
+        Confirmation::TypeName => (Context::Input, key.typed_char()),
+        Confirmation::Prompt | Confirmation::None => (Context::Confirm, None),
+    };
+    let action = match typed {
+        Some(_) => None,
+        None => app.press_key(&[context], key),
+    };
+    let confirm = app.confirm.as_mut()?;
+    let answer = match (action, typed) {
+        (Some((_, Action::Quit)), _) => {
+            app.quit();
+            return None;
         }
-        Err(e) => {
-            app.add_log("ERROR", format!("Failed to get metrics: {}", e));
+        (Some((_, Action::Confirm)), _) => confirm.is_satisfied().then_some(true),
+        (Some((_, Action::Cancel)), _) => Some(false),
+        (Some((_, Action::DeleteChar)), _) => {
+            confirm.input.pop();
+            None
         }
+        (None, Some(c)) => {
+            confirm.input.push(c);
+            None
+        }
+        _ => None,
+    }?;
+    let action = confirm.action;
+    app.confirm = None;
+    Some((action, answer))
+}
+
+/// Whether a control action can be sent now, logging why not
+fn ready_for_action(app: &mut App) -> bool {
+    if app.connection_status != ConnectionStatus::Connected {
+        let message = match app.keymap.hint(Context::Global, &[Action::Connect]) {
+            Some(key) => format!("Not connected - press '{}' to connect", key),
+            None => "Not connected".to_string(),
+        };
+        app.add_log(LogLevel::Warn, message);
+        return false;
     }
+    if let Some(pending) = app.pending_action {
+        app.add_log(
+            LogLevel::Warn,
+            format!("{} still in progress", pending.label()),
+        );
+        return false;
+    }
+    true
+}
+
+/// Send a control action to the daemon
+fn send_action(app: &mut App, worker: &ClientWorker, action: ControlAction) {
+    app.add_log(LogLevel::Info, format!("Executing: {}", action.label()));
+    app.pending_action = Some(action);
+    worker.send(Command::Control(action));
 }
@@ -0,0 +1,312 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use daemon_controller::grpc::daemon::MetricsResponse;
+
+/// Longest window that can be displayed, and so the longest history kept
+const MAX_HISTORY_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Hard cap on stored samples (one hour at four samples per second)
+const MAX_SAMPLES: usize = 4 * 60 * 60;
+
+/// Chart points as `(seconds relative to now, value)`
+pub type Series = Vec<(f64, f64)>;
+
+/// A metrics response and when it was received
+#[derive(Debug, Clone)]
+pub struct MetricsSample {
+    pub at: Instant,
+    pub metrics: MetricsResponse,
+}
+
+/// Time window shown in the metrics history view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HistoryWindow {
+    #[default]
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+}
+
+impl HistoryWindow {
+    pub fn next(self) -> Self {
+        match self {
+            Self::OneMinute => Self::FiveMinutes,
+            Self::FiveMinutes => Self::FifteenMinutes,
+            Self::FifteenMinutes => Self::OneHour,
+            Self::OneHour => Self::OneMinute,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::OneHour => "1h",
+        }
+    }
+
+    pub fn duration(self) -> Duration {
+        match self {
+            Self::OneMinute => Duration::from_secs(60),
+            Self::FiveMinutes => Duration::from_secs(5 * 60),
+            Self::FifteenMinutes => Duration::from_secs(15 * 60),
+            Self::OneHour => Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Span of recent samples that rates displayed in the panel are averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Relative change below which a rate is considered steady
+const TREND_THRESHOLD: f64 = 0.05;
+
+/// Per-second request and error rates over an interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rates {
+    pub requests_per_sec: f64,
+    pub errors_per_sec: f64,
+    /// Fraction of requests that failed, if any requests were made
+    pub error_ratio: Option<f64>,
+}
+
+impl Rates {
+    /// Compute rates between consecutive samples, if time has passed
+    pub fn between(prev: &MetricsSample, cur: &MetricsSample) -> Option<Self> {
+        Self::over(&[prev, cur])
+    }
+
+    /// Compute rates across a run of samples, oldest first.
+    ///
+    /// Counter increases are summed pair by pair so a reset part-way through
+    /// (e.g. after a daemon restart) only loses the requests made before it.
+    fn over(samples: &[&MetricsSample]) -> Option<Self> {
+        let (first, last) = (samples.first()?, samples.last()?);
+        let elapsed = last.at.checked_duration_since(first.at)?.as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        let (requests, errors) = samples.windows(2).fold((0, 0), |(requests, errors), pair| {
+            let (prev, cur) = (&pair[0].metrics, &pair[1].metrics);
+            (
+                requests + counter_delta(prev.requests_total, cur.requests_total),
+                errors + counter_delta(prev.errors_total, cur.errors_total),
+            )
+        });
+        Some(Self {
+            requests_per_sec: requests as f64 / elapsed,
+            errors_per_sec: errors as f64 / elapsed,
+            error_ratio: (requests > 0).then(|| errors as f64 / requests as f64),
+        })
+    }
+}
+
+/// Increase of a monotonic counter, treating a decrease as a reset to zero
+fn counter_delta(prev: u64, cur: u64) -> u64 {
+    if cur >= prev {
+        cur - prev
+    } else {
+        cur
+    }
+}
+
+/// Direction a rate moved compared to the preceding interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    fn between(previous: f64, current: f64) -> Self {
+        let scale = previous.abs().max(current.abs());
+        if scale == 0.0 || (current - previous).abs() / scale < TREND_THRESHOLD {
+            Self::Steady
+        } else if current > previous {
+            Self::Rising
+        } else {
+            Self::Falling
+        }
+    }
+
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Self::Rising => "↑",
+            Self::Falling => "↓",
+            Self::Steady => "→",
+        }
+    }
+}
+
+/// Recent rates with their trend against the interval before
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DerivedRates {
+    pub current: Rates,
+    pub requests_trend: Trend,
+    pub errors_trend: Trend,
+    pub error_ratio_trend: Trend,
+}
+
+/// Bounded ring buffer of timestamped metrics samples
+#[derive(Debug, Default)]
+pub struct MetricsHistory {
+    samples: VecDeque<MetricsSample>,
+}
+
+impl MetricsHistory {
+    /// Record a new sample, evicting samples older than the longest window
+    pub fn push(&mut self, metrics: MetricsResponse) {
+        let now = Instant::now();
+        while let Some(oldest) = self.samples.front() {
+            if now.duration_since(oldest.at) > MAX_HISTORY_AGE || self.samples.len() >= MAX_SAMPLES
+            {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.samples.push_back(MetricsSample { at: now, metrics });
+    }
+
+    /// Samples received within the window, oldest first
+    pub fn window(&self, window: HistoryWindow) -> impl Iterator<Item = &MetricsSample> {
+        let cutoff = Instant::now().checked_sub(window.duration());
+        self.samples
+            .iter()
+            .filter(move |s| cutoff.is_none_or(|cutoff| s.at >= cutoff))
+    }
+
+    /// Extract chart points for one metric
+    pub fn series(&self, window: HistoryWindow, value: impl Fn(&MetricsResponse) -> f64) -> Series {
+        let now = Instant::now();
+        self.window(window)
+            .map(|s| (-age_secs(now, s.at), value(&s.metrics)))
+            .collect()
+    }
+
+    /// Rates over the most recent interval, with trends against the one before
+    pub fn derived_rates(&self) -> Option<DerivedRates> {
+        let now = Instant::now();
+        let recent_start = now.checked_sub(RATE_WINDOW)?;
+        let previous_start = recent_start.checked_sub(RATE_WINDOW);
+
+        // Include the last sample before each interval so no increase is lost
+        let in_range = |from: Instant, to: Option<Instant>| -> Vec<&MetricsSample> {
+            let start = self
+                .samples
+                .partition_point(|s| s.at < from)
+                .saturating_sub(1);
+            self.samples
+                .range(start..)
+                .take_while(|s| to.is_none_or(|to| s.at <= to))
+                .collect()
+        };
+
+        let current = Rates::over(&in_range(recent_start, None))?;
+        let previous = previous_start
+            .and_then(|start| Rates::over(&in_range(start, Some(recent_start))))
+            .unwrap_or(current);
+
+        Some(DerivedRates {
+            current,
+            requests_trend: Trend::between(previous.requests_per_sec, current.requests_per_sec),
+            errors_trend: Trend::between(previous.errors_per_sec, current.errors_per_sec),
+            error_ratio_trend: Trend::between(
+                previous.error_ratio.unwrap_or_default(),
+                current.error_ratio.unwrap_or_default(),
+            ),
+        })
+    }
+
+    /// Extract request and error rate points for charting
+    pub fn rate_series(&self, window: HistoryWindow) -> (Series, Series) {
+        let now = Instant::now();
+        let samples: Vec<&MetricsSample> = self.window(window).collect();
+        samples
+            .windows(2)
+            .filter_map(|pair| {
+                let rates = Rates::between(pair[0], pair[1])?;
+                let x = -age_secs(now, pair[1].at);
+                Some(((x, rates.requests_per_sec), (x, rates.errors_per_sec)))
+            })
+            .unzip()
+    }
+}
+
+fn age_secs(now: Instant, at: Instant) -> f64 {
+    now.saturating_duration_since(at).as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(start: Instant, secs: u64, requests_total: u64, errors_total: u64) -> MetricsSample {
+        MetricsSample {
+            at: start + Duration::from_secs(secs),
+            metrics: MetricsResponse {
+                requests_total,
+                errors_total,
+                ..MetricsResponse::default()
+            },
+        }
+    }
+
+    #[test]
+    fn counter_increase() {
+        assert_eq!(counter_delta(100, 150), 50);
+        assert_eq!(counter_delta(100, 100), 0);
+    }
+
+    #[test]
+    fn counter_reset() {
+        assert_eq!(counter_delta(100, 30), 30);
+        assert_eq!(counter_delta(100, 0), 0);
+    }
+
+    #[test]
+    fn rates_between_samples() {
+        let start = Instant::now();
+        let rates = Rates::between(&sample(start, 0, 100, 10), &sample(start, 10, 300, 30))
+            .expect("time passed");
+        assert_eq!(rates.requests_per_sec, 20.0);
+        assert_eq!(rates.errors_per_sec, 2.0);
+        assert_eq!(rates.error_ratio, Some(0.1));
+    }
+
+    #[test]
+    fn rates_across_a_reset() {
+        let start = Instant::now();
+        let samples = [
+            sample(start, 0, 1000, 100),
+            sample(start, 5, 1100, 110),
+            // The daemon restarted and counts from zero again
+            sample(start, 10, 40, 0),
+            sample(start, 15, 100, 10),
+        ];
+        let samples: Vec<&MetricsSample> = samples.iter().collect();
+        let rates = Rates::over(&samples).expect("time passed");
+        assert_eq!(rates.requests_per_sec, 200.0 / 15.0);
+        assert_eq!(rates.errors_per_sec, 20.0 / 15.0);
+        assert_eq!(rates.error_ratio, Some(0.1));
+    }
+
+    #[test]
+    fn no_rates_without_elapsed_time_or_requests() {
+        let start = Instant::now();
+        let first = sample(start, 0, 100, 0);
+        assert!(Rates::between(&first, &sample(start, 0, 200, 0)).is_none());
+        assert!(Rates::over(&[]).is_none());
+
+        let idle = Rates::between(&first, &sample(start, 10, 100, 0)).expect("time passed");
+        assert_eq!(idle.requests_per_sec, 0.0);
+        assert_eq!(idle.error_ratio, None);
+    }
+}
@@ -0,0 +1,544 @@
+//! In-process mock implementation of `DaemonService`.
+//!
+//! Simulates a daemon lifecycle, drifting metrics and a stream of synthetic
+//! logs, with knobs to inject latency, errors and disconnects. Used by the
+//! `mock-daemon` binary and by tests.
+
+mod simulation;
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+#[cfg(unix)]
+use std::{
+    fs, io,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+};
+
+use tokio::{
+    sync::{broadcast, mpsc, watch},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::ReceiverStream;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+use tonic::{
+    transport::{Server, ServerTlsConfig},
+    Request, Response, Status,
+};
+
+use crate::grpc::daemon::{
+    daemon_service_server::{DaemonService, DaemonServiceServer},
+    ControlCommand, ControlRequest, ControlResponse, DaemonState, LogEntry, LogsRequest,
+    MetricsRequest, MetricsResponse, StatusRequest, StatusResponse,
+};
+use simulation::{SimLog, Simulation};
+
+/// Number of log entries kept for `tail_lines` replay
+const LOG_HISTORY: usize = 1000;
+
+/// Tunable behaviour of the mock daemon
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// Version string reported by GetStatus
+    pub version: String,
+    /// Delay added before every unary response
+    pub latency: Duration,
+    /// Probability in `[0, 1]` that a unary call fails with `INTERNAL`
+    pub error_rate: f64,
+    /// Interval between synthetic log lines
+    pub log_interval: Duration,
+    /// Time an accepted control command leaves the state unchanged before
+    /// its transitions begin
+    pub control_delay: Duration,
+    /// Bearer token required on every call, if any
+    pub auth_token: Option<String>,
+    /// Bearer token accepted for reads but denied for control commands
+    pub read_only_token: Option<String>,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            version: format!("{}-mock", env!("CARGO_PKG_VERSION")),
+            latency: Duration::ZERO,
+            error_rate: 0.0,
+            log_interval: Duration::from_millis(500),
+            control_delay: Duration::ZERO,
+            auth_token: None,
+            read_only_token: None,
+        }
+    }
+}
+
+/// State shared between the service, its log generator and handles
+struct Shared {
+    config: Mutex<MockConfig>,
+    sim: Mutex<Simulation>,
+    history: Mutex<VecDeque<LogEntry>>,
+    logs: broadcast::Sender<LogEntry>,
+    /// Bumped to drop every open connection
+    disconnects: watch::Sender<u64>,
+}
+
+impl Shared {
+    fn publish(&self, logs: Vec<SimLog>) {
+        if logs.is_empty() {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut history = self.history.lock().unwrap();
+        for (level, source, message) in logs {
+            let entry = LogEntry {
+                timestamp,
+                level: level.into(),
+                message,
+                source: source.to_string(),
+            };
+            if history.len() == LOG_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(entry.clone());
+            // No subscribers is fine
+            let _ = self.logs.send(entry);
+        }
+    }
+
+    /// Check the request's bearer token when authentication is enabled.
+    ///
+    /// Control commands are refused for the read-only token.
+    fn authorize<T>(&self, request: &Request<T>, control: bool) -> Result<(), Denied> {
+        let config = self.config.lock().unwrap();
+        if config.auth_token.is_none() && config.read_only_token.is_none() {
+            return Ok(());
+        }
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(Denied::MissingToken)?;
+
+        if config.auth_token.as_deref() == Some(token) {
+            Ok(())
+        } else if config.read_only_token.as_deref() != Some(token) {
+            Err(Denied::InvalidToken)
+        } else if control {
+            Err(Denied::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Apply configured latency and error injection before a unary call
+    async fn inject_faults(&self) -> Result<(), Status> {
+        let (latency, error_rate) = {
+            let config = self.config.lock().unwrap();
+            (config.latency, config.error_rate)
+        };
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        if self.sim.lock().unwrap().rng.unit() < error_rate {
+            return Err(Status::internal("Injected failure"));
+        }
+        Ok(())
+    }
+}
+
+/// Reasons a call is refused when authentication is enabled
+#[derive(Debug, Clone, Copy)]
+enum Denied {
+    MissingToken,
+    InvalidToken,
+    ReadOnly,
+}
+
+impl From<Denied> for Status {
+    fn from(denied: Denied) -> Self {
+        match denied {
+            Denied::MissingToken => Status::unauthenticated("Missing bearer token"),
+            Denied::InvalidToken => Status::unauthenticated("Invalid bearer token"),
+            Denied::ReadOnly => Status::permission_denied("Token is read-only"),
+        }
+    }
+}
+
+/// Mock `DaemonService` implementation.
+///
+/// Spawns a log generator task on creation, so it must be created inside a
+/// Tokio runtime. The generator stops when the daemon is dropped.
+pub struct MockDaemon {
+    shared: Arc<Shared>,
+    generator: JoinHandle<()>,
+}
+
+impl MockDaemon {
+    /// Create a mock daemon in the running state
+    pub fn new(config: MockConfig) -> Self {
+        let (logs, _) = broadcast::channel(LOG_HISTORY);
+        let (disconnects, _) = watch::channel(0);
+        let shared = Arc::new(Shared {
+            config: Mutex::new(config),
+            sim: Mutex::new(Simulation::new()),
+            history: Mutex::new(VecDeque::with_capacity(LOG_HISTORY)),
+            logs,
+            disconnects,
+        });
+
+        let generator = tokio::spawn(generate_logs(Arc::clone(&shared)));
+        Self { shared, generator }
+    }
+
+    /// Get a handle for adjusting the mock while it is being served
+    pub fn handle(&self) -> MockHandle {
+        MockHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Wrap the mock in a tonic service
+    pub fn into_service(self) -> DaemonServiceServer<Self> {
+        DaemonServiceServer::new(self)
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        self.generator.abort();
+    }
+}
+
+/// Emit synthetic log lines while the daemon is active
+async fn generate_logs(shared: Arc<Shared>) {
+    loop {
+        let interval = shared.config.lock().unwrap().log_interval;
+        tokio::time::sleep(interval).await;
+
+        let logs = {
+            let mut sim = shared.sim.lock().unwrap();
+            let mut logs = sim.advance();
+            if sim.is_active() {
+                logs.push(sim.random_log());
+            }
+            logs
+        };
+        shared.publish(logs);
+    }
+}
+
+/// Handle for adjusting a running mock daemon
+#[derive(Clone)]
+pub struct MockHandle {
+    shared: Arc<Shared>,
+}
+
+impl MockHandle {
+    /// Set the delay added before every unary response
+    pub fn set_latency(&self, latency: Duration) {
+        self.shared.config.lock().unwrap().latency = latency;
+    }
+
+    /// Set the probability that a unary call fails
+    pub fn set_error_rate(&self, error_rate: f64) {
+        self.shared.config.lock().unwrap().error_rate = error_rate;
+    }
+
+    /// Force the simulated daemon into a state
+    pub fn set_state(&self, state: DaemonState) {
+        let logs = self.shared.sim.lock().unwrap().set_state(state);
+        self.shared.publish(logs);
+    }
+
+    /// Drop all open connections.
+    ///
+    /// Log streams end with `UNAVAILABLE` and [`serve`] stops accepting
+    /// connections for its configured downtime.
+    pub fn disconnect(&self) {
+        self.shared
+            .disconnects
+            .send_modify(|generation| *generation += 1);
+    }
+}
+
+#[tonic::async_trait]
+impl DaemonService for MockDaemon {
+    async fn get_status(
+        &self,
+        request: Request<StatusRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        self.shared.authorize(&request, false)?;
+        self.shared.inject_faults().await?;
+
+        let (response, logs) = {
+            let mut sim = self.shared.sim.lock().unwrap();
+            let logs = sim.advance();
+            let state = sim.state();
+            let response = StatusResponse {
+                state: state.into(),
+                version: self.shared.config.lock().unwrap().version.clone(),
+                uptime_seconds: sim.uptime_seconds(),
+                message: format!("Mock daemon is {}", state.label().to_lowercase()),
+            };
+            (response, logs)
+        };
+        self.shared.publish(logs);
+        Ok(Response::new(response))
+    }
+
+    async fn get_metrics(
+        &self,
+        request: Request<MetricsRequest>,
+    ) -> Result<Response<MetricsResponse>, Status> {
+        self.shared.authorize(&request, false)?;
+        self.shared.inject_faults().await?;
+
+        let (metrics, logs) = {
+            let mut sim = self.shared.sim.lock().unwrap();
+            let logs = sim.advance();
+            (sim.sample_metrics(), logs)
+        };
+        self.shared.publish(logs);
+        Ok(Response::new(metrics))
+    }
+
+    async fn control(
+        &self,
+        request: Request<ControlRequest>,
+    ) -> Result<Response<ControlResponse>, Status> {
+        self.shared.authorize(&request, true)?;
+        self.shared.inject_faults().await?;
+
+        let command = ControlCommand::try_from(request.into_inner().command)
+            .map_err(|_| Status::invalid_argument("Unknown control command"))?;
+        let delay = self.shared.config.lock().unwrap().control_delay;
+        let (success, message, logs) = self.shared.sim.lock().unwrap().control(command, delay);
+        self.shared.publish(logs);
+        Ok(Response::new(ControlResponse { success, message }))
+    }
+
+    type StreamLogsStream = ReceiverStream<Result<LogEntry, Status>>;
+
+    async fn stream_logs(
+        &self,
+        request: Request<LogsRequest>,
+    ) -> Result<Response<Self::StreamLogsStream>, Status> {
+        self.shared.authorize(&request, false)?;
+        let request = request.into_inner();
+        let min_level = request.min_level;
+        let mut live = self.shared.logs.subscribe();
+        let mut disconnects = self.shared.disconnects.subscribe();
+        let tail: Vec<LogEntry> = {
+            let history = self.shared.history.lock().unwrap();
+            let matching: Vec<&LogEntry> =
+                history.iter().filter(|e| e.level >= min_level).collect();
+            let skip = matching.len().saturating_sub(request.tail_lines as usize);
+            matching.into_iter().skip(skip).cloned().collect()
+        };
+
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            for entry in tail {
+                if tx.send(Ok(entry)).await.is_err() {
+                    return;
+                }
+            }
+            loop {
+                tokio::select! {
+                    received = live.recv() => match received {
+                        Ok(entry) if entry.level >= min_level => {
+                            if tx.send(Ok(entry)).await.is_err() {
+                                return;
+                            }
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                    _ = disconnects.changed() => {
+                        let _ = tx.send(Err(Status::unavailable("Injected disconnect"))).await;
+                        return;
+                    }
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Where [`serve`] accepts connections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    /// Unix domain socket at the given path
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for ListenAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Serve a mock daemon on `addr` until the returned future is dropped.
+///
+/// Whenever [`MockHandle::disconnect`] is called the server shuts down, stays
+/// offline for `downtime`, then starts listening again. Connections use TLS
+/// when `tls` is given. A stale socket file left at a Unix socket path is
+/// replaced.
+pub async fn serve(
+    addr: impl Into<ListenAddr>,
+    daemon: MockDaemon,
+    downtime: Duration,
+    tls: Option<ServerTlsConfig>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr = addr.into();
+    let mut disconnects = daemon.shared.disconnects.subscribe();
+    let service = daemon.into_service();
+    loop {
+        let shutdown = async {
+            let _ = disconnects.changed().await;
+        };
+        let mut builder = Server::builder();
+        if let Some(tls) = &tls {
+            builder = builder.tls_config(tls.clone())?;
+        }
+        let router = builder.add_service(service.clone());
+        match &addr {
+            ListenAddr::Tcp(addr) => router.serve_with_shutdown(*addr, shutdown).await?,
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                remove_stale_socket(path)?;
+                let incoming = UnixListenerStream::new(tokio::net::UnixListener::bind(path)?);
+                router
+                    .serve_with_incoming_shutdown(incoming, shutdown)
+                    .await?
+            }
+        }
+        tokio::time::sleep(downtime).await;
+    }
+}
+
+/// Remove a socket left behind by an earlier server, refusing to touch
+/// anything that is not a socket
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use tonic::Code;
+
+    use super::*;
+    use crate::grpc::{daemon::LogLevel, DaemonClient, LogStreamEvent};
+
+    async fn connect(address: &str) -> DaemonClient {
+        let mut client = DaemonClient::new(address.to_string());
+        for _ in 0..100 {
+            if client.connect().await.is_ok() {
+                return client;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("mock daemon did not accept connections");
+    }
+
+    #[tokio::test]
+    async fn error_rate_fails_calls() {
+        let daemon = MockDaemon::new(MockConfig {
+            error_rate: 1.0,
+            ..MockConfig::default()
+        });
+        let status = daemon
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .expect_err("injected failure");
+        assert_eq!(status.code(), Code::Internal);
+        assert_eq!(status.message(), "Injected failure");
+        let control = Request::new(ControlRequest {
+            command: ControlCommand::Stop.into(),
+        });
+        assert!(daemon.control(control).await.is_err());
+        // The failed command was not applied
+        daemon.handle().set_error_rate(0.0);
+        let status = daemon
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .expect("status");
+        assert_eq!(status.get_ref().state(), DaemonState::Running);
+    }
+
+    #[tokio::test]
+    async fn disconnect_drops_connections_for_the_downtime() {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("pick a free port");
+        let address = format!("http://{}", addr);
+        let daemon = MockDaemon::new(MockConfig {
+            log_interval: Duration::from_millis(20),
+            ..MockConfig::default()
+        });
+        let handle = daemon.handle();
+        let server = tokio::spawn(serve(addr, daemon, Duration::from_secs(1), None));
+
+        let mut client = connect(&address).await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        client
+            .subscribe_logs(LogLevel::Debug, 0, tx)
+            .expect("subscribe");
+        let first = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await;
+        assert!(matches!(first, Ok(Some(LogStreamEvent::Entry(_)))));
+
+        handle.disconnect();
+        let ended = async {
+            while let Some(event) = rx.recv().await {
+                if !matches!(event, LogStreamEvent::Entry(_)) {
+                    return event;
+                }
+            }
+            panic!("log stream task stopped");
+        };
+        let event = tokio::time::timeout(Duration::from_secs(5), ended)
+            .await
+            .expect("log stream ended");
+        assert!(matches!(event, LogStreamEvent::Dropped(_)), "{:?}", event);
+
+        // New connections are refused until the downtime is over
+        assert!(DaemonClient::new(address.clone()).connect().await.is_err());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        connect(&address).await.get_status().await.expect("status");
+        server.abort();
+    }
+}
@@ -0,0 +1,423 @@
+use std::{
+    collections::{hash_map::RandomState, VecDeque},
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant},
+};
+
+use crate::grpc::daemon::{ControlCommand, DaemonState, LogLevel, MetricsResponse};
+
+/// Time spent in each intermediate state (Starting, Stopping)
+const TRANSITION_TIME: Duration = Duration::from_secs(1);
+
+/// Memory limit reported by the simulated daemon
+const MEMORY_LIMIT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Resident memory right after startup
+const BASELINE_MEMORY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// CPU usage the random walk is pulled towards
+const CPU_MEAN_PERCENT: f64 = 30.0;
+
+/// Log messages emitted by the simulation
+pub type SimLog = (LogLevel, &'static str, String);
+
+/// Simulated daemon lifecycle and resource usage
+#[derive(Debug)]
+pub struct Simulation {
+    state: DaemonState,
+    /// States still to pass through, entered one `TRANSITION_TIME` apart
+    steps: VecDeque<DaemonState>,
+    next_step_at: Instant,
+    started_at: Option<Instant>,
+    last_sample: Instant,
+    cpu_usage_percent: f64,
+    memory_bytes: f64,
+    connections_active: f64,
+    requests_total: f64,
+    errors_total: f64,
+    pub rng: Rng,
+}
+
+impl Simulation {
+    /// Create a simulation of a daemon that is already running
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            state: DaemonState::Running,
+            steps: VecDeque::new(),
+            next_step_at: now,
+            started_at: Some(now),
+            last_sample: now,
+            cpu_usage_percent: CPU_MEAN_PERCENT,
+            memory_bytes: BASELINE_MEMORY_BYTES as f64,
+            connections_active: 10.0,
+            requests_total: 0.0,
+            errors_total: 0.0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Current state after applying any due transitions
+    pub fn state(&self) -> DaemonState {
+        self.state
+    }
+
+    /// Seconds since the daemon last entered the running state
+    pub fn uptime_seconds(&self) -> u64 {
+        match self.state {
+            DaemonState::Running => self
+                .started_at
+                .map(|t| t.elapsed().as_secs())
+                .unwrap_or_default(),
+            _ => 0,
+        }
+    }
+
+    /// Whether the daemon is doing work and producing logs
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            DaemonState::Starting | DaemonState::Running | DaemonState::Stopping
+        )
+    }
+
+    /// Apply transitions that are due, returning log lines describing them
+    pub fn advance(&mut self) -> Vec<SimLog> {
+        let mut logs = Vec::new();
+        let now = Instant::now();
+        while now >= self.next_step_at {
+            let Some(state) = self.steps.pop_front() else {
+                break;
+            };
+            self.enter(state, &mut logs);
+            self.next_step_at += TRANSITION_TIME;
+        }
+        logs
+    }
+
+    /// Force the daemon into a state, cancelling pending transitions
+    pub fn set_state(&mut self, state: DaemonState) -> Vec<SimLog> {
+        let mut logs = Vec::new();
+        self.steps.clear();
+        self.enter(state, &mut logs);
+        logs
+    }
+
+    /// Apply a control command, starting its transitions after `delay`.
+    ///
+    /// Returns whether the command was accepted, a response message, and log
+    /// lines describing what happened.
+    pub fn control(
+        &mut self,
+        command: ControlCommand,
+        delay: Duration,
+    ) -> (bool, String, Vec<SimLog>) {
+        let mut logs = self.advance();
+        if !self.steps.is_empty() {
+            let message = format!("Daemon is busy ({})", self.state.label());
+            return (false, message, logs);
+        }
+
+        use DaemonState::*;
+        let steps: &[DaemonState] = match (command, self.state) {
+            (ControlCommand::Start, Running) => {
+                return (false, "Daemon is already running".to_string(), logs);
+            }
+            (ControlCommand::Start, _) => &[Starting, Running],
+            (ControlCommand::Stop, Running) => &[Stopping, Stopped],
+            (ControlCommand::Stop, _) => {
+                return (false, "Daemon is not running".to_string(), logs);
+            }
+            (ControlCommand::Restart, Running) => &[Stopping, Starting, Running],
+            (ControlCommand::Restart, _) => &[Starting, Running],
+            (ControlCommand::Reload, Running) => {
+                logs.push((
+                    LogLevel::Info,
+                    "config",
+                    "Configuration reloaded".to_string(),
+                ));
+                return (true, "Configuration reloaded".to_string(), logs);
+            }
+            (ControlCommand::Reload, _) => {
+                return (false, "Daemon is not running".to_string(), logs);
+            }
+            (ControlCommand::Unspecified, _) => {
+                return (false, "Unspecified command".to_string(), logs);
+            }
+        };
+
+        self.steps.extend(steps.iter().copied());
+        self.next_step_at = Instant::now() + delay;
+        logs.extend(self.advance());
+
+        let message = format!("{} initiated", command.as_str_name());
+        (true, message, logs)
+    }
+
+    fn enter(&mut self, state: DaemonState, logs: &mut Vec<SimLog>) {
+        self.state = state;
+        match state {
+            DaemonState::Starting => {
+                // A fresh process starts its counters from zero
+                self.requests_total = 0.0;
+                self.errors_total = 0.0;
+                self.memory_bytes = BASELINE_MEMORY_BYTES as f64;
+            }
+            DaemonState::Running => self.started_at = Some(Instant::now()),
+            DaemonState::Stopped | DaemonState::Error => {
+                self.started_at = None;
+                self.cpu_usage_percent = 0.0;
+                self.memory_bytes = 0.0;
+                self.connections_active = 0.0;
+            }
+            _ => {}
+        }
+        let level = if state == DaemonState::Error {
+            LogLevel::Error
+        } else {
+            LogLevel::Info
+        };
+        logs.push((level, "lifecycle", format!("Daemon {}", state.label())));
+    }
+
+    /// Sample metrics, letting them drift since the previous sample
+    pub fn sample_metrics(&mut self) -> MetricsResponse {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_sample).as_secs_f64();
+        self.last_sample = now;
+
+        if self.is_active() {
+            let noise = self.rng.range(-6.0, 6.0);
+            self.cpu_usage_percent += (CPU_MEAN_PERCENT - self.cpu_usage_percent) * 0.1 + noise;
+            self.cpu_usage_percent = self.cpu_usage_percent.clamp(1.0, 100.0);
+
+            let growth = self.rng.range(-4.0, 5.0) * 1024.0 * 1024.0;
+            self.memory_bytes = (self.memory_bytes + growth).clamp(
+                BASELINE_MEMORY_BYTES as f64 / 2.0,
+                MEMORY_LIMIT_BYTES as f64,
+            );
+
+            self.connections_active =
+                (self.connections_active + self.rng.range(-3.0, 3.0)).clamp(0.0, 500.0);
+
+            let request_rate = self.connections_active * self.rng.range(1.0, 3.0);
+            let requests = request_rate * dt;
+            self.requests_total += requests;
+            self.errors_total += requests * self.rng.range(0.0, 0.02);
+        }
+
+        MetricsResponse {
+            cpu_usage_percent: self.cpu_usage_percent,
+            memory_bytes: self.memory_bytes as u64,
+            memory_limit_bytes: MEMORY_LIMIT_BYTES,
+            connections_active: self.connections_active as u64,
+            requests_total: self.requests_total as u64,
+            errors_total: self.errors_total as u64,
+        }
+    }
+
+    /// Produce a synthetic application log line
+    pub fn random_log(&mut self) -> SimLog {
+        const PATHS: [&str; 4] = ["/api/items", "/api/users", "/healthz", "/api/orders"];
+        let path = PATHS[self.rng.index(PATHS.len())];
+        let roll = self.rng.unit();
+
+        if roll < 0.05 {
+            (
+                LogLevel::Error,
+                "db",
+                format!(
+                    "query failed table=orders error=\"connection reset\" retry={}",
+                    self.rng.index(3) + 1
+                ),
+            )
+        } else if roll < 0.15 {
+            (
+                LogLevel::Warn,
+                "http",
+                format!(
+                    "slow request method=GET path={} duration_ms={}",
+                    path,
+                    500 + self.rng.index(1500)
+                ),
+            )
+        } else if roll < 0.40 {
+            (
+                LogLevel::Debug,
+                "cache",
+                format!("cache lookup key={} hit={}", path, roll < 0.3),
+            )
+        } else {
+            (
+                LogLevel::Info,
+                "http",
+                format!(
+                    "request completed method=GET path={} status=200 duration_ms={}",
+                    path,
+                    5 + self.rng.index(80)
+                ),
+            )
+        }
+    }
+}
+
+/// Small xorshift generator; the mock does not need cryptographic randomness
+#[derive(Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        // Never seed with zero, which xorshift cannot leave
+        Self(RandomState::new().build_hasher().finish() | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Random value in `[0, 1)`
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Random value in `[low, high)`
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.unit()
+    }
+
+    /// Random index below `len`
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply the next pending transition without waiting for it
+    fn step(sim: &mut Simulation) -> Vec<SimLog> {
+        sim.next_step_at = Instant::now();
+        sim.advance()
+    }
+
+    fn messages(logs: &[SimLog]) -> Vec<&str> {
+        logs.iter()
+            .map(|(_, _, message)| message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn restart_passes_through_stopping_and_starting() {
+        let mut sim = Simulation::new();
+        let (accepted, message, logs) = sim.control(ControlCommand::Restart, Duration::ZERO);
+        assert!(accepted);
+        assert_eq!(message, "CONTROL_COMMAND_RESTART initiated");
+        assert_eq!(messages(&logs), ["Daemon Stopping"]);
+        assert_eq!(sim.state(), DaemonState::Stopping);
+
+        // Each later state is entered a transition time apart
+        assert!(sim.advance().is_empty());
+        assert_eq!(messages(&step(&mut sim)), ["Daemon Starting"]);
+        assert_eq!(sim.uptime_seconds(), 0);
+        assert_eq!(messages(&step(&mut sim)), ["Daemon Running"]);
+        assert_eq!(sim.state(), DaemonState::Running);
+        assert!(step(&mut sim).is_empty());
+    }
+
+    #[test]
+    fn stop_and_start() {
+        let mut sim = Simulation::new();
+        sim.control(ControlCommand::Stop, Duration::ZERO);
+        step(&mut sim);
+        assert_eq!(sim.state(), DaemonState::Stopped);
+        assert!(!sim.is_active());
+        assert_eq!(sim.sample_metrics().cpu_usage_percent, 0.0);
+
+        let (accepted, _, logs) = sim.control(ControlCommand::Start, Duration::ZERO);
+        assert!(accepted);
+        assert_eq!(messages(&logs), ["Daemon Starting"]);
+        step(&mut sim);
+        assert_eq!(sim.state(), DaemonState::Running);
+    }
+
+    #[test]
+    fn delayed_commands_start_later() {
+        let mut sim = Simulation::new();
+        let (accepted, _, logs) = sim.control(ControlCommand::Stop, Duration::from_secs(60));
+        assert!(accepted);
+        assert!(logs.is_empty());
+        assert_eq!(sim.state(), DaemonState::Running);
+        assert_eq!(messages(&step(&mut sim)), ["Daemon Stopping"]);
+    }
+
+    #[test]
+    fn commands_are_rejected_while_busy() {
+        let mut sim = Simulation::new();
+        sim.control(ControlCommand::Restart, Duration::ZERO);
+        for command in [ControlCommand::Stop, ControlCommand::Reload] {
+            let (accepted, message, _) = sim.control(command, Duration::ZERO);
+            assert!(!accepted);
+            assert_eq!(message, "Daemon is busy (Stopping)");
+        }
+
+        // Also while a delayed command waits to begin
+        let mut sim = Simulation::new();
+        sim.control(ControlCommand::Stop, Duration::from_secs(60));
+        let (accepted, message, _) = sim.control(ControlCommand::Restart, Duration::ZERO);
+        assert!(!accepted);
+        assert_eq!(message, "Daemon is busy (Running)");
+    }
+
+    #[test]
+    fn commands_valid_only_in_some_states() {
+        let mut sim = Simulation::new();
+        let (accepted, message, logs) = sim.control(ControlCommand::Reload, Duration::ZERO);
+        assert!(accepted);
+        assert_eq!(message, "Configuration reloaded");
+        assert_eq!(messages(&logs), ["Configuration reloaded"]);
+        assert_eq!(sim.state(), DaemonState::Running);
+
+        let result = |sim: &mut Simulation, command| {
+            let (accepted, message, _) = sim.control(command, Duration::ZERO);
+            (accepted, message)
+        };
+        assert_eq!(
+            result(&mut sim, ControlCommand::Start),
+            (false, "Daemon is already running".to_string())
+        );
+        assert_eq!(
+            result(&mut sim, ControlCommand::Unspecified),
+            (false, "Unspecified command".to_string())
+        );
+
+        sim.set_state(DaemonState::Stopped);
+        for command in [ControlCommand::Stop, ControlCommand::Reload] {
+            assert_eq!(
+                result(&mut sim, command),
+                (false, "Daemon is not running".to_string())
+            );
+        }
+
+        // A failed daemon can be restarted
+        sim.set_state(DaemonState::Error);
+        assert!(result(&mut sim, ControlCommand::Restart).0);
+        assert_eq!(sim.state(), DaemonState::Starting);
+    }
+
+    #[test]
+    fn set_state_cancels_transitions() {
+        let mut sim = Simulation::new();
+        sim.control(ControlCommand::Restart, Duration::ZERO);
+        let logs = sim.set_state(DaemonState::Error);
+        assert_eq!(
+            logs,
+            [(LogLevel::Error, "lifecycle", "Daemon Error".to_string())]
+        );
+        assert!(step(&mut sim).is_empty());
+        assert_eq!(sim.state(), DaemonState::Error);
+    }
+}
@@ -0,0 +1,101 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::app::App;
+use crate::audit::AuditRecord;
+
+/// Render the table of recorded control commands, newest first
+pub fn render_audit(frame: &mut Frame, app: &App, area: Rect) {
+    let path = app
+        .audit_file
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    let mut block = Block::default()
+        .title(format!(
+            " Audit Trail ({} commands) - {} ",
+            app.audit_records.len(),
+            path
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    // Messages are often cut off in the table, so show the selected one in full
+    if let Some(record) = app.audit_records.get(app.audit_selected) {
+        block = block.title_bottom(format!(" {} ", record.message));
+    }
+
+    if app.audit_records.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            "No control commands recorded yet",
+            Style::default().fg(Color::DarkGray),
+        ))
+        .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let header = Row::new([
+        "Time", "User", "Origin", "Daemon", "Command", "Result", "Latency", "Message",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    );
+    let rows: Vec<Row> = app.audit_records.iter().map(record_row).collect();
+    // Size the free-text columns to their contents
+    let width = |header: &str, value: &dyn Fn(&AuditRecord) -> usize| {
+        let widest = app.audit_records.iter().map(value).max().unwrap_or(0);
+        Constraint::Length(widest.max(header.len()) as u16)
+    };
+    let widths = [
+        Constraint::Length(19),
+        width("User", &|r| {
+            r.user.chars().count() + r.host.chars().count() + 1
+        }),
+        Constraint::Length(9),
+        width("Daemon", &|r| r.daemon().chars().count()),
+        Constraint::Length(7),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Fill(1),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(Some(app.audit_selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn record_row(record: &AuditRecord) -> Row<'_> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| record.timestamp.clone());
+    let result = if record.success {
+        Span::styled("✔ Success", Style::default().fg(Color::Green))
+    } else {
+        Span::styled("✖ Failed", Style::default().fg(Color::Red))
+    };
+
+    Row::new([
+        Cell::from(timestamp),
+        Cell::from(format!("{}@{}", record.user, record.host)),
+        Cell::from(record.origin.label()),
+        Cell::from(record.daemon()),
+        Cell::from(record.command.as_str()),
+        Cell::from(result),
+        Cell::from(format!("{}ms", record.latency_ms)),
+        Cell::from(record.message.as_str()),
+    ])
+}
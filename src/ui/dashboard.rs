@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -6,7 +8,21 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, ConnectionStatus, ControlAction, FocusedPanel};
+use super::audit_view::render_audit;
+use super::fleet_view::render_fleet;
+use super::help_view::render_help;
+use super::metrics_view::render_metrics_history;
+use super::popup::{render_confirm, render_log_detail};
+use crate::alerts::AlertTransition;
+use crate::app::{
+    App, ConnectionStatus, ControlAction, FocusedPanel, LogEntry, LogOrigin, TransportSecurity,
+    View,
+};
+use crate::fleet::Health;
+use crate::keymap::{Action, Chord, Context, Keymap};
+use crate::log_filter::{InputTarget, LogOrder, Query};
+use crate::metrics::Trend;
+use daemon_controller::grpc::{daemon::LogLevel, socket_path};
 
 /// Render the main dashboard
 pub fn render_dashboard(frame: &mut Frame, app: &App) {
@@ -21,20 +37,100 @@ pub fn render_dashboard(frame: &mut Frame, app: &App) {
         .split(frame.area());
 
     render_header(frame, app, chunks[0]);
-    render_main_content(frame, app, chunks[1]);
+    match app.view {
+        View::Dashboard => render_main_content(frame, app, chunks[1]),
+        View::MetricsHistory => render_metrics_history(frame, app, chunks[1]),
+        View::Fleet => render_fleet(frame, app, chunks[1]),
+        View::Audit => render_audit(frame, app, chunks[1]),
+        View::LogFile => render_logs_panel(frame, app, chunks[1]),
+    }
     render_footer(frame, app, chunks[2]);
+    render_confirm(frame, app);
+    render_log_detail(frame, app);
+    render_help(frame, app);
 }
 
 /// Render the header with title and connection status
 fn render_header(frame: &mut Frame, app: &App, area: Rect) {
+    if let (View::Fleet, Some(fleet)) = (app.view, &app.fleet) {
+        let down = fleet
+            .members
+            .iter()
+            .filter(|m| matches!(m.health, Health::Down { .. }))
+            .count();
+        let mut spans = vec![
+            Span::styled(
+                " Daemon Controller ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" | "),
+            Span::raw(format!(" Fleet of {} daemons ", fleet.members.len())),
+            Span::raw(" | "),
+            Span::styled(
+                format!(" {} up ", fleet.up_count()),
+                Style::default().fg(Color::Green),
+            ),
+        ];
+        if down > 0 {
+            spans.push(Span::styled(
+                format!(" {} down ", down),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        let header = Paragraph::new(Line::from(spans)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+        frame.render_widget(header, area);
+        return;
+    }
+    if let (View::LogFile, Some(path)) = (app.view, &app.viewed_log_file) {
+        let header = Paragraph::new(Line::from(vec![
+            Span::styled(
+                " Daemon Controller ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" | "),
+            Span::raw(format!(" {} ", path.display())),
+            Span::raw(" | "),
+            Span::styled(" read-only ", Style::default().fg(Color::Yellow)),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+        frame.render_widget(header, area);
+        return;
+    }
+
     let (status_text, status_color) = match &app.connection_status {
-        ConnectionStatus::Connected => ("Connected", Color::Green),
-        ConnectionStatus::Connecting => ("Connecting...", Color::Yellow),
-        ConnectionStatus::Disconnected => ("Disconnected", Color::Red),
-        ConnectionStatus::Error(msg) => (msg.as_str(), Color::Red),
+        ConnectionStatus::Connected => ("Connected".to_string(), Color::Green),
+        ConnectionStatus::Connecting => (format!("{} Connecting...", app.spinner()), Color::Yellow),
+        ConnectionStatus::Reconnecting {
+            attempt,
+            next_retry,
+        } => {
+            let wait = next_retry.saturating_duration_since(Instant::now());
+            (
+                format!(
+                    "Reconnecting (attempt {}, retry in {}s)",
+                    attempt,
+                    wait.as_secs_f64().ceil() as u64
+                ),
+                Color::Yellow,
+            )
+        }
+        ConnectionStatus::Disconnected => ("Disconnected".to_string(), Color::Red),
+        ConnectionStatus::Error(msg) => (msg.clone(), Color::Red),
     };
 
-    let header = Paragraph::new(Line::from(vec![
+    let mut spans = vec![
         Span::styled(
             " Daemon Controller ",
             Style::default()
@@ -47,9 +143,50 @@ fn render_header(frame: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(status_color),
         ),
         Span::raw(" | "),
-        Span::raw(format!(" {} ", app.daemon_address)),
-    ]))
-    .block(
+    ];
+    if let Some(profile) = &app.profile {
+        spans.push(Span::styled(
+            format!(" [{}]", profile),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    match app.security {
+        TransportSecurity::Plaintext => {}
+        TransportSecurity::Tls => {
+            spans.push(Span::styled(" 🔒", Style::default().fg(Color::Green)))
+        }
+        TransportSecurity::TlsUnverified => spans.push(Span::styled(
+            " 🔓 unverified",
+            Style::default().fg(Color::Yellow),
+        )),
+        TransportSecurity::LocalSocket => {
+            spans.push(Span::styled(" unix", Style::default().fg(Color::Cyan)))
+        }
+    }
+    let address = socket_path(&app.daemon_address).map_or_else(
+        || app.daemon_address.clone(),
+        |path| path.display().to_string(),
+    );
+    spans.push(Span::raw(format!(" {} ", address)));
+    if let Some(label) = app.auth_failure.as_ref().and_then(|e| e.auth_label()) {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!(" {} ", label),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if app.alerts.any_firing() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            " ALERT ",
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let header = Paragraph::new(Line::from(spans)).block(
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Cyan)),
@@ -64,7 +201,7 @@ fn render_main_content(frame: &mut Frame, app: &App, area: Rect) {
         .direction(Direction::Horizontal)
         .constraints([
             Constraint::Percentage(40), // Left: Status + Metrics
-            Constraint::Percentage(30), // Center: Controls
+            Constraint::Percentage(30), // Center: Controls + Alerts
             Constraint::Percentage(30), // Right: Logs
         ])
         .split(area);
@@ -78,8 +215,17 @@ fn render_main_content(frame: &mut Frame, app: &App, area: Rect) {
     render_status_panel(frame, app, left_chunks[0]);
     render_metrics_panel(frame, app, left_chunks[1]);
 
-    // Center: Controls
-    render_controls_panel(frame, app, chunks[1]);
+    // Center: Controls and Alerts
+    let center_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(ControlAction::ALL.len() as u16 + 2),
+            Constraint::Min(3),
+        ])
+        .split(chunks[1]);
+
+    render_controls_panel(frame, app, center_chunks[0]);
+    render_alerts_panel(frame, app, center_chunks[1]);
 
     // Right: Logs
     render_logs_panel(frame, app, chunks[2]);
@@ -90,6 +236,8 @@ fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
     let is_focused = app.focused_panel == FocusedPanel::Status;
     let border_style = if is_focused {
         Style::default().fg(Color::Yellow)
+    } else if app.alerts.firing_for(true) {
+        Style::default().fg(Color::Red)
     } else {
         Style::default().fg(Color::White)
     };
@@ -119,7 +267,11 @@ fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
     let status_block = Paragraph::new(status_info)
         .block(
             Block::default()
-                .title(" Status ")
+                .title(if app.refreshing {
+                    format!(" Status {} ", app.spinner())
+                } else {
+                    " Status ".to_string()
+                })
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -134,7 +286,11 @@ fn render_metrics_panel(frame: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
             .title(" Metrics ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::White));
+            .border_style(if app.alerts.firing_for(false) {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::White)
+            });
         let inner = block.inner(area);
         frame.render_widget(block, area);
         inner
@@ -178,10 +334,41 @@ fn render_metrics_panel(frame: &mut Frame, app: &App, area: Rect) {
         frame.render_widget(memory_gauge, chunks[1]);
 
         // Stats
+        let rates = app.metrics_history.derived_rates();
+        let mut requests_line = vec![Span::raw(format!("Requests: {}", metrics.requests_total))];
+        let mut errors_line = vec![Span::raw(format!("Errors: {}", metrics.errors_total))];
+        if let Some(rates) = rates {
+            requests_line.push(Span::styled(
+                format!(
+                    "  {:.1}/s {}",
+                    rates.current.requests_per_sec,
+                    rates.requests_trend.arrow()
+                ),
+                Style::default().fg(Color::Cyan),
+            ));
+            errors_line.push(Span::styled(
+                format!(
+                    "  {:.1}/s {}",
+                    rates.current.errors_per_sec,
+                    rates.errors_trend.arrow()
+                ),
+                trend_style(rates.errors_trend),
+            ));
+            if let Some(ratio) = rates.current.error_ratio {
+                errors_line.push(Span::styled(
+                    format!(
+                        "  ({:.2}% {})",
+                        ratio * 100.0,
+                        rates.error_ratio_trend.arrow()
+                    ),
+                    trend_style(rates.error_ratio_trend),
+                ));
+            }
+        }
         let stats = Paragraph::new(vec![
             Line::from(format!("Connections: {}", metrics.connections_active)),
-            Line::from(format!("Requests: {}", metrics.requests_total)),
-            Line::from(format!("Errors: {}", metrics.errors_total)),
+            Line::from(requests_line),
+            Line::from(errors_line),
         ]);
         frame.render_widget(stats, chunks[2]);
     } else {
@@ -216,7 +403,12 @@ fn render_controls_panel(frame: &mut Frame, app: &App, area: Rect) {
             } else {
                 Style::default()
             };
-            ListItem::new(format!("  {}  ", action.label())).style(style)
+            let marker = if app.pending_action == Some(*action) {
+                app.spinner()
+            } else {
+                " "
+            };
+            ListItem::new(format!(" {} {}  ", marker, action.label())).style(style)
         })
         .collect();
 
@@ -230,6 +422,106 @@ fn render_controls_panel(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(controls_list, area);
 }
 
+/// Render the alerts panel with firing rules and recent transitions
+fn render_alerts_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let firing: Vec<ListItem> = app
+        .alerts
+        .rules
+        .iter()
+        .filter_map(|state| {
+            let since = state.firing_since?;
+            Some(ListItem::new(Line::from(vec![
+                Span::styled(
+                    "FIRING ",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("{} ({}s)", state.rule, since.elapsed().as_secs())),
+            ])))
+        })
+        .collect();
+    let firing_count = firing.len();
+
+    let history = app.alerts.history.iter().rev().map(|event| {
+        let (label, color) = match event.transition {
+            AlertTransition::Fired => ("fired   ", Color::Red),
+            AlertTransition::Resolved => ("resolved", Color::Green),
+            AlertTransition::NoData => ("no data ", Color::DarkGray),
+        };
+        let value = event
+            .value
+            .map(|v| format!(" = {:.2}", v))
+            .unwrap_or_default();
+        ListItem::new(Line::from(vec![
+            Span::styled(
+                format!("[{}] ", event.timestamp),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{} ", label), Style::default().fg(color)),
+            Span::raw(format!("{}{}", event.rule, value)),
+        ]))
+    });
+
+    let items: Vec<ListItem> = firing.into_iter().chain(history).collect();
+    let border_style = if firing_count > 0 {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::White)
+    };
+
+    let alerts_list = List::new(items).block(
+        Block::default()
+            .title(format!(
+                " Alerts ({} firing / {} rules) ",
+                firing_count,
+                app.alerts.rules.len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+
+    frame.render_widget(alerts_list, area);
+}
+
+/// Widest source column in the logs panel; longer sources are cut
+const MAX_SOURCE_WIDTH: usize = 16;
+
+/// Shown in the source column for the controller's own messages
+const CONTROLLER_SOURCE: &str = "ctl";
+
+/// Width of the time column of the logs panel, e.g. `[12:00:00] `
+const CLOCK_WIDTH: usize = 11;
+
+/// Width of the level column of the logs panel, e.g. `ERROR `
+const LEVEL_WIDTH: usize = 6;
+
+/// Rows an entry takes in the logs panel, one per line of its message
+fn log_rows(entry: &LogEntry) -> usize {
+    entry.message.lines().count().max(1)
+}
+
+/// First entry to draw in a logs panel of `page` rows so that the
+/// `selected` entry is in view, moving the `previous` first entry as little
+/// as possible. The panel is kept full when the newest entries fit.
+fn log_top(visible: &[&LogEntry], selected: usize, previous: usize, page: usize) -> usize {
+    if visible.is_empty() {
+        return 0;
+    }
+    let mut top = previous.min(selected);
+    // Scroll down until the selected entry fits below the top
+    let mut used: usize = visible[top..=selected].iter().map(|e| log_rows(e)).sum();
+    while used > page && top < selected {
+        used -= log_rows(visible[top]);
+        top += 1;
+    }
+    // Scroll up while the entries from the top leave room at the bottom
+    let mut below: usize = visible[top..].iter().map(|e| log_rows(e)).sum();
+    while top > 0 && below + log_rows(visible[top - 1]) <= page {
+        top -= 1;
+        below += log_rows(visible[top]);
+    }
+    top
+}
+
 /// Render the logs panel
 fn render_logs_panel(frame: &mut Frame, app: &App, area: Rect) {
     let is_focused = app.focused_panel == FocusedPanel::Logs;
@@ -239,66 +531,346 @@ fn render_logs_panel(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::White)
     };
 
-    let items: Vec<ListItem> = app
-        .logs
+    let visible = app.visible_logs();
+    let page = area.height.saturating_sub(2) as usize;
+    app.log_page.set(page);
+    let selected = app.log_selected(visible.len());
+    let top = log_top(&visible, selected, app.log_top.get(), page);
+    app.log_top.set(top);
+    let search = app.log_filter.search.as_ref();
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+    // Controller messages have no source to size the column by
+    let source_width = visible
         .iter()
-        .skip(app.log_scroll)
-        .take(area.height.saturating_sub(2) as usize)
-        .map(|log| {
-            let level_color = match log.level.as_str() {
-                "ERROR" => Color::Red,
-                "WARN" => Color::Yellow,
-                "INFO" => Color::Green,
-                _ => Color::Gray,
-            };
-            ListItem::new(Line::from(vec![
+        .map(|log| log.source.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_SOURCE_WIDTH);
+    // Continuation lines start under the first line of the message
+    let indent =
+        " ".repeat(CLOCK_WIDTH + LEVEL_WIDTH + source_width + usize::from(source_width > 0));
+    let mut rows = 0;
+    let items: Vec<ListItem> = visible
+        .iter()
+        .enumerate()
+        .skip(top)
+        .take_while(|(_, log)| {
+            let fits = rows < page;
+            rows += log_rows(log);
+            fits
+        })
+        .map(|(index, log)| {
+            let level_color = level_color(log.level);
+            let mut spans = vec![
                 Span::styled(
-                    format!("[{}] ", log.timestamp),
+                    format!("[{}] ", log.clock()),
                     Style::default().fg(Color::DarkGray),
                 ),
                 Span::styled(
-                    format!("{:<5} ", log.level),
+                    format!("{:<5} ", log.level.label()),
                     Style::default().fg(level_color),
                 ),
-                Span::raw(&log.message),
-            ]))
+            ];
+            // Set the controller's own messages apart from the daemon's
+            let (source, source_style, message_style) = match log.origin {
+                LogOrigin::Controller => {
+                    let italic = Style::default().add_modifier(Modifier::ITALIC);
+                    (CONTROLLER_SOURCE, italic.fg(Color::DarkGray), italic)
+                }
+                LogOrigin::Daemon => (
+                    log.source.as_str(),
+                    Style::default().fg(source_color(&log.source)),
+                    Style::default(),
+                ),
+            };
+            if source_width > 0 {
+                // Cut long sources so messages stay aligned
+                let source = match source.char_indices().nth(source_width) {
+                    Some((end, _)) => &source[..end],
+                    None => source,
+                };
+                spans.extend(highlight_matches(source, search, source_style, highlight));
+                spans.push(Span::raw(format!(
+                    "{:1$} ",
+                    "",
+                    source_width - source.chars().count()
+                )));
+            }
+            let mut message = log.message.lines();
+            spans.extend(highlight_matches(
+                message.next().unwrap_or_default(),
+                search,
+                message_style,
+                highlight,
+            ));
+            let mut lines = vec![Line::from(spans)];
+            lines.extend(message.map(|text| {
+                let mut spans = vec![Span::raw(indent.as_str())];
+                spans.extend(highlight_matches(text, search, message_style, highlight));
+                Line::from(spans)
+            }));
+            let item = ListItem::new(lines);
+            if is_focused && index == selected {
+                item.style(Style::default().bg(Color::DarkGray))
+            } else {
+                item
+            }
         })
         .collect();
 
-    let logs_list = List::new(items).block(
-        Block::default()
-            .title(format!(" Logs ({}) ", app.logs.len()))
-            .borders(Borders::ALL)
-            .border_style(border_style),
-    );
+    let mut title = if app.log_filter.is_active() {
+        format!(" Logs ({}/{})", visible.len(), app.logs.len())
+    } else {
+        format!(" Logs ({})", app.logs.len())
+    };
+    if let Some(level) = app.log_filter.min_level {
+        title.push_str(&format!(" | {}+", level.label()));
+    }
+    if app.log_filter.order == LogOrder::Level {
+        title.push_str(" | by level");
+    }
+    if let Some(source) = &app.log_filter.source {
+        title.push_str(&format!(" | source {}", source));
+    }
+    if let Some(filter) = &app.log_filter.filter {
+        title.push_str(&format!(" | filter {}", filter.display()));
+    }
+    if let Some(search) = search {
+        let matches: Vec<usize> = visible
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| search.matches(entry))
+            .map(|(i, _)| i)
+            .collect();
+        match matches.iter().position(|&i| i == selected) {
+            Some(n) => title.push_str(&format!(
+                " | {} {}/{}",
+                search.display(),
+                n + 1,
+                matches.len()
+            )),
+            None => title.push_str(&format!(
+                " | {} ({} matches)",
+                search.display(),
+                matches.len()
+            )),
+        }
+    }
+    title.push(' ');
+
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border_style);
+    if let Some(input) = &app.log_filter.input {
+        let prompt = match input.target {
+            InputTarget::Search => "/",
+            InputTarget::Filter => "filter: ",
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(" {}{}_ ", prompt, input.text),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(
+                if input.regex { "[regex] " } else { "[text] " },
+                Style::default().fg(Color::Cyan),
+            ),
+        ];
+        if let Some(error) = &input.error {
+            spans.push(Span::styled(
+                format!("{} ", error),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        block = block.title_bottom(Line::from(spans));
+    }
+    if !app.log_follow {
+        let follow = app
+            .keymap
+            .hint(Context::LogsPanel, &[Action::Bottom])
+            .map(|key| format!(" - {} to follow", key))
+            .unwrap_or_default();
+        let indicator = if app.log_unseen > 0 {
+            format!(" ↓ {} new lines{} ", app.log_unseen, follow)
+        } else {
+            format!(" Paused{} ", follow)
+        };
+        block = block.title_bottom(
+            Line::from(Span::styled(indicator, Style::default().fg(Color::Cyan))).right_aligned(),
+        );
+    }
+    let logs_list = List::new(items).block(block);
 
     frame.render_widget(logs_list, area);
 }
 
+/// Colour of a log level
+pub(super) fn level_color(level: LogLevel) -> Color {
+    match level {
+        LogLevel::Error => Color::Red,
+        LogLevel::Warn => Color::Yellow,
+        LogLevel::Info => Color::Green,
+        LogLevel::Debug => Color::Magenta,
+    }
+}
+
+/// Colour of a log source, the same for every entry from it
+pub(super) fn source_color(source: &str) -> Color {
+    // Level colours are left out so sources are not mistaken for levels
+    const PALETTE: [Color; 6] = [
+        Color::Blue,
+        Color::Cyan,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightGreen,
+        Color::LightCyan,
+    ];
+    // FNV-1a, so colours stay the same between runs
+    let hash = source.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+/// Split `text` into spans, highlighting the matches of the search
+fn highlight_matches<'a>(
+    text: &'a str,
+    search: Option<&Query>,
+    style: Style,
+    highlight: Style,
+) -> Vec<Span<'a>> {
+    let ranges = search.map(|q| q.find_ranges(text)).unwrap_or_default();
+    let mut spans = Vec::with_capacity(ranges.len() * 2 + 1);
+    let mut end = 0;
+    for range in ranges {
+        if range.start > end {
+            spans.push(Span::styled(&text[end..range.start], style));
+        }
+        spans.push(Span::styled(&text[range.clone()], highlight));
+        end = range.end;
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(&text[end..], style));
+    }
+    spans
+}
+
 /// Render the footer with keybindings
 fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let mut hints = Hints::new(&app.keymap);
     let keybindings = if let Some(msg) = &app.status_message {
         Line::from(Span::styled(
             msg.clone(),
             Style::default().fg(Color::Yellow),
         ))
-    } else {
+    } else if !app.pending_keys.is_empty() {
+        let chord = Chord {
+            keys: app.pending_keys.clone(),
+        };
         Line::from(vec![
-            Span::styled(" q ", Style::default().fg(Color::Red)),
-            Span::raw("Quit"),
-            Span::raw(" | "),
-            Span::styled(" Tab ", Style::default().fg(Color::Cyan)),
-            Span::raw("Switch Panel"),
-            Span::raw(" | "),
-            Span::styled(" c ", Style::default().fg(Color::Green)),
-            Span::raw("Connect"),
-            Span::raw(" | "),
-            Span::styled(" Enter ", Style::default().fg(Color::Yellow)),
-            Span::raw("Execute"),
-            Span::raw(" | "),
-            Span::styled(" j/k ", Style::default().fg(Color::Magenta)),
-            Span::raw("Navigate"),
+            Span::styled(format!(" {} ", chord), Style::default().fg(Color::Yellow)),
+            Span::raw("…"),
         ])
+    } else if let (View::Fleet, Some(fleet)) = (app.view, &app.fleet) {
+        if fleet.menu.is_some() {
+            let menu = Context::BulkMenu;
+            hints.add(menu, &[Action::Down, Action::Up], "Action", Color::Magenta);
+            hints.add(menu, &[Action::Mode], "Mode", Color::Blue);
+            hints.add(
+                menu,
+                &[Action::GrowBatch, Action::ShrinkBatch],
+                "Batch Size",
+                Color::Cyan,
+            );
+            hints.add(menu, &[Action::Run], "Run", Color::Yellow);
+            hints.add(menu, &[Action::Close], "Close", Color::Red);
+        } else {
+            let fleet_keys = Context::Fleet;
+            hints.add(fleet_keys, &[Action::Quit], "Quit", Color::Red);
+            hints.add(fleet_keys, &[Action::Help], "Help", Color::Cyan);
+            hints.add(
+                fleet_keys,
+                &[Action::Down, Action::Up],
+                "Navigate",
+                Color::Magenta,
+            );
+            hints.add(fleet_keys, &[Action::Mark], "Mark", Color::Cyan);
+            hints.add(fleet_keys, &[Action::MarkAll], "Mark All", Color::Cyan);
+            hints.add(fleet_keys, &[Action::BulkMenu], "Bulk Action", Color::Green);
+            hints.add(fleet_keys, &[Action::Open], "Open Dashboard", Color::Yellow);
+            if fleet.bulk_running() {
+                hints.add(fleet_keys, &[Action::CancelBulk], "Cancel Bulk", Color::Red);
+            }
+        }
+        hints.line()
+    } else if app.log_detail.is_some() {
+        let detail = Context::LogDetail;
+        hints.add(
+            detail,
+            &[Action::Down, Action::Up],
+            "Scroll",
+            Color::Magenta,
+        );
+        hints.add(detail, &[Action::Close], "Close", Color::Red);
+        hints.line()
+    } else if app.log_filter.input.is_some() {
+        let input = Context::Input;
+        hints.add(input, &[Action::Confirm], "Apply", Color::Yellow);
+        hints.add(input, &[Action::ToggleRegex], "Text/Regex", Color::Cyan);
+        hints.add(input, &[Action::Cancel], "Cancel", Color::Red);
+        hints.line()
+    } else {
+        let global = Context::Global;
+        hints.add(global, &[Action::Quit], "Quit", Color::Red);
+        hints.add(global, &[Action::Help], "Help", Color::Cyan);
+        if app.view != View::LogFile {
+            hints.add(global, &[Action::NextPanel], "Switch Panel", Color::Cyan);
+        }
+        // The logs panel has too many keys of its own to fit the global ones
+        if app.focused_panel == FocusedPanel::Logs {
+            let logs = Context::LogsPanel;
+            hints.add(logs, &[Action::Down, Action::Up], "Scroll", Color::Magenta);
+            hints.add(logs, &[Action::Search], "Search", Color::Yellow);
+            hints.add(
+                logs,
+                &[Action::NextMatch, Action::PrevMatch],
+                "Next/Prev",
+                Color::Yellow,
+            );
+            hints.add(logs, &[Action::Filter], "Filter", Color::Yellow);
+            hints.add(logs, &[Action::Level], "Level", Color::Yellow);
+            hints.add(logs, &[Action::Source], "Source", Color::Yellow);
+            hints.add(logs, &[Action::Order], "Sort", Color::Yellow);
+            hints.add(logs, &[Action::Details], "Details", Color::Yellow);
+            hints.add(
+                logs,
+                &[Action::ExportShown, Action::ExportAll],
+                "Export",
+                Color::Yellow,
+            );
+        } else {
+            let controls = Context::ControlsPanel;
+            hints.add(global, &[Action::Connect], "Connect", Color::Green);
+            hints.add(controls, &[Action::Execute], "Execute", Color::Yellow);
+            hints.add(
+                controls,
+                &[Action::Down, Action::Up],
+                "Navigate",
+                Color::Magenta,
+            );
+            hints.add(
+                global,
+                &[Action::MetricsHistory],
+                "Metrics History",
+                Color::Blue,
+            );
+            hints.add(global, &[Action::Audit], "Audit", Color::Blue);
+        }
+        if app.fleet.is_some() && !app.captures_keys() {
+            hints.add(global, &[Action::Fleet], "Fleet", Color::Cyan);
+        }
+        hints.line()
     };
 
     let footer = Paragraph::new(keybindings).block(Block::default().borders(Borders::ALL));
@@ -306,8 +878,51 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(footer, area);
 }
 
+/// Key hints of the footer, showing the keys as the user has bound them
+struct Hints<'a> {
+    keymap: &'a Keymap,
+    spans: Vec<Span<'static>>,
+}
+
+impl<'a> Hints<'a> {
+    fn new(keymap: &'a Keymap) -> Self {
+        Self {
+            keymap,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Hint for the first keys of `actions`, left out if they are unbound
+    fn add(&mut self, context: Context, actions: &[Action], text: &'static str, color: Color) {
+        let Some(keys) = self.keymap.hint(context, actions) else {
+            return;
+        };
+        if !self.spans.is_empty() {
+            self.spans.push(Span::raw(" | "));
+        }
+        self.spans.push(Span::styled(
+            format!(" {} ", keys),
+            Style::default().fg(color),
+        ));
+        self.spans.push(Span::raw(text));
+    }
+
+    fn line(self) -> Line<'static> {
+        Line::from(self.spans)
+    }
+}
+
+/// Colour for an error trend: rising is bad, falling is good
+fn trend_style(trend: Trend) -> Style {
+    match trend {
+        Trend::Rising => Style::default().fg(Color::Red),
+        Trend::Falling => Style::default().fg(Color::Green),
+        Trend::Steady => Style::default().fg(Color::Gray),
+    }
+}
+
 /// Format bytes to human-readable string
-fn format_bytes(bytes: u64) -> String {
+pub(super) fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -0,0 +1,319 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::dashboard::format_bytes;
+use super::popup::{centered_rect, render_frame};
+use crate::app::{App, ControlAction};
+use crate::bulk::{BulkMenu, BulkMode, BulkOperation, TargetStatus};
+use crate::fleet::{Fleet, FleetMember, Health};
+use daemon_controller::grpc::{daemon::DaemonState, socket_path};
+
+/// Render the table of fleet members
+pub fn render_fleet(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(fleet) = &app.fleet else {
+        return;
+    };
+
+    let mut columns = vec![
+        "", "Daemon", "Address", "State", "Version", "Uptime", "CPU", "Memory", "Errors", "Health",
+    ];
+    if fleet.operation.is_some() {
+        columns.push("Bulk");
+    }
+    let header = Row::new(columns).style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    );
+    let rows: Vec<Row> = fleet
+        .members
+        .iter()
+        .enumerate()
+        .map(|(index, member)| member_row(app, fleet, index, member))
+        .collect();
+    // Size the free-text columns to their contents
+    let width = |header: &str, value: &dyn Fn(&FleetMember) -> usize| {
+        let widest = fleet.members.iter().map(value).max().unwrap_or(0);
+        Constraint::Length(widest.max(header.len()) as u16)
+    };
+    let mut widths = vec![
+        Constraint::Length(3),
+        width("Daemon", &|m| m.name.chars().count()),
+        width("Address", &|m| display_address(m).chars().count()),
+        Constraint::Length(8),
+        width("Version", &|m| {
+            m.status.as_ref().map_or(0, |s| s.version.chars().count())
+        }),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(15),
+        Constraint::Length(14),
+        Constraint::Fill(1),
+    ];
+    if fleet.operation.is_some() {
+        widths.push(Constraint::Length(11));
+    }
+
+    let mut block = Block::default()
+        .title(format!(
+            " Fleet ({} up / {}) ",
+            fleet.up_count(),
+            fleet.members.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    if let Some(operation) = &fleet.operation {
+        block = block.title(operation_title(app, operation));
+    }
+    // The table only has room for a summary, so show the full error below
+    if let Some(error) = selected_error(fleet) {
+        block = block.title_bottom(Span::styled(
+            format!(" {} ", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(Some(fleet.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+
+    if let Some(menu) = &fleet.menu {
+        render_bulk_menu(frame, fleet, menu, area);
+    }
+}
+
+/// Error to show for the selected row, preferring a failed bulk action
+fn selected_error(fleet: &Fleet) -> Option<&str> {
+    let bulk = fleet
+        .operation
+        .as_ref()
+        .and_then(|op| op.targets.get(&fleet.selected));
+    if let Some(TargetStatus::Failed(error)) = bulk {
+        return Some(error);
+    }
+    match fleet.members.get(fleet.selected).map(|m| &m.health) {
+        Some(Health::Down { error, .. }) => Some(error),
+        _ => None,
+    }
+}
+
+/// Title describing the current or most recent bulk action
+fn operation_title(app: &App, operation: &BulkOperation) -> Span<'static> {
+    let mode = match operation.mode {
+        BulkMode::Parallel => operation.mode.label().to_string(),
+        BulkMode::Rolling => format!("{}, batch {}", operation.mode.label(), operation.batch_size),
+    };
+    let (prefix, color) = if !operation.finished {
+        (app.spinner().to_string(), Color::Yellow)
+    } else if operation
+        .targets
+        .values()
+        .all(|s| matches!(s, TargetStatus::Succeeded(_)))
+    {
+        ("✔".to_string(), Color::Green)
+    } else {
+        ("✖".to_string(), Color::Red)
+    };
+    Span::styled(
+        format!(" {} {} ({}) ", prefix, operation.summary(), mode),
+        Style::default().fg(color),
+    )
+}
+
+/// Render the bulk action menu over the table
+fn render_bulk_menu(frame: &mut Frame, fleet: &Fleet, menu: &BulkMenu, area: Rect) {
+    let popup = centered_rect(area, 44, ControlAction::ALL.len() as u16 + 5);
+    let inner = render_frame(
+        frame,
+        popup,
+        format!(" Bulk Action on {} daemons ", fleet.marked.len()),
+        Color::Green,
+    );
+
+    let [actions, options] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(2)]).areas(inner);
+    let items: Vec<ListItem> = ControlAction::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            if i == menu.selected_action {
+                ListItem::new(format!("> {}", action.label())).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ListItem::new(format!("  {}", action.label()))
+            }
+        })
+        .collect();
+    frame.render_widget(List::new(items), actions);
+
+    let batch = match menu.mode {
+        BulkMode::Parallel => Span::styled("all at once", Style::default().fg(Color::DarkGray)),
+        BulkMode::Rolling => Span::raw(menu.batch_size.to_string()),
+    };
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("Mode:  ", Style::default().fg(Color::Cyan)),
+            Span::raw(menu.mode.label()),
+        ]),
+        Line::from(vec![
+            Span::styled("Batch: ", Style::default().fg(Color::Cyan)),
+            batch,
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(lines), options);
+}
+
+/// Address as shown in the header of the dashboard
+fn display_address(member: &FleetMember) -> String {
+    socket_path(&member.address)
+        .map_or_else(|| member.address.clone(), |path| path.display().to_string())
+}
+
+fn member_row<'a>(app: &App, fleet: &Fleet, index: usize, member: &'a FleetMember) -> Row<'a> {
+    let missing = || Cell::from(Span::styled("-", Style::default().fg(Color::DarkGray)));
+    let mark = if fleet.marked.contains(&index) {
+        Cell::from(Span::styled("[x]", Style::default().fg(Color::Cyan)))
+    } else {
+        Cell::from("[ ]")
+    };
+
+    let (state, version, uptime) = match &member.status {
+        Some(status) => {
+            let state = DaemonState::try_from(status.state).unwrap_or_default();
+            (
+                Cell::from(Span::styled(state.label(), state_style(state))),
+                Cell::from(status.version.as_str()),
+                Cell::from(format_uptime(status.uptime_seconds)),
+            )
+        }
+        None => (missing(), missing(), missing()),
+    };
+
+    let (cpu, memory) = match &member.metrics {
+        Some(metrics) => {
+            let percent = if metrics.memory_limit_bytes > 0 {
+                metrics.memory_bytes as f64 / metrics.memory_limit_bytes as f64 * 100.0
+            } else {
+                0.0
+            };
+            (
+                Cell::from(format!("{:.1}%", metrics.cpu_usage_percent)),
+                Cell::from(format!(
+                    "{} ({:.0}%)",
+                    format_bytes(metrics.memory_bytes),
+                    percent
+                )),
+            )
+        }
+        None => (missing(), missing()),
+    };
+
+    let errors = match &member.rates {
+        Some(rates) => {
+            let text = match rates.error_ratio {
+                Some(ratio) => format!("{:.1}/s ({:.1}%)", rates.errors_per_sec, ratio * 100.0),
+                None => format!("{:.1}/s", rates.errors_per_sec),
+            };
+            let style = if rates.errors_per_sec > 0.0 {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Cell::from(Span::styled(text, style))
+        }
+        None => missing(),
+    };
+
+    let health = match &member.health {
+        Health::Pending => Cell::from(Span::styled(
+            format!("{} Polling", app.spinner()),
+            Style::default().fg(Color::Yellow),
+        )),
+        Health::Up { latency } => Cell::from(Span::styled(
+            format!("● Up {}", format_latency(*latency)),
+            Style::default().fg(Color::Green),
+        )),
+        Health::Down { summary, .. } => Cell::from(Span::styled(
+            format!("✖ {}", summary),
+            Style::default().fg(Color::Red),
+        )),
+    };
+
+    let mut cells = vec![
+        mark,
+        Cell::from(member.name.as_str()),
+        Cell::from(display_address(member)),
+        state,
+        version,
+        uptime,
+        cpu,
+        memory,
+        errors,
+        health,
+    ];
+    if let Some(operation) = &fleet.operation {
+        cells.push(match operation.targets.get(&index) {
+            Some(status) => bulk_cell(app, status),
+            None => Cell::from(""),
+        });
+    }
+    Row::new(cells)
+}
+
+fn bulk_cell(app: &App, status: &TargetStatus) -> Cell<'static> {
+    let (text, color) = match status {
+        TargetStatus::Queued => ("Queued".to_string(), Color::DarkGray),
+        TargetStatus::Sending => (format!("{} Sending", app.spinner()), Color::Yellow),
+        TargetStatus::Settling => (format!("{} Settling", app.spinner()), Color::Yellow),
+        TargetStatus::Succeeded(_) => ("✔ Done".to_string(), Color::Green),
+        TargetStatus::Failed(_) => ("✖ Failed".to_string(), Color::Red),
+        TargetStatus::Skipped => ("Skipped".to_string(), Color::DarkGray),
+        TargetStatus::Cancelled => ("Cancelled".to_string(), Color::DarkGray),
+    };
+    Cell::from(Span::styled(text, Style::default().fg(color)))
+}
+
+fn state_style(state: DaemonState) -> Style {
+    match state {
+        DaemonState::Running => Style::default().fg(Color::Green),
+        DaemonState::Starting | DaemonState::Stopping => Style::default().fg(Color::Yellow),
+        DaemonState::Error => Style::default().fg(Color::Red),
+        DaemonState::Stopped | DaemonState::Unknown => Style::default().fg(Color::DarkGray),
+    }
+}
+
+/// Format an uptime with its two most significant units, e.g. "3h 05m"
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours) = (seconds / 86_400, seconds / 3600 % 24);
+    let (minutes, seconds) = (seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn format_latency(latency: Duration) -> String {
+    if latency < Duration::from_millis(1) {
+        format!("{}µs", latency.as_micros())
+    } else {
+        format!("{}ms", latency.as_millis())
+    }
+}
@@ -0,0 +1,91 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::popup::render_frame;
+use crate::app::App;
+
+/// Shown in place of the keys of an action with none
+const UNBOUND: &str = "unbound";
+
+/// Render the full-screen list of key bindings, if the help is open.
+///
+/// The list is drawn from the keymap that key handling uses, one group per
+/// place the keys apply, along with the names to rebind them by.
+pub fn render_help(frame: &mut Frame, app: &App) {
+    let Some(help) = &app.help else {
+        return;
+    };
+
+    let bindings = || app.keymap.groups().flat_map(|(_, bindings)| bindings);
+    let width = bindings()
+        .map(|binding| binding.label().chars().count().max(UNBOUND.len()))
+        .max()
+        .unwrap_or(0);
+    let description_width = bindings()
+        .map(|binding| binding.description.chars().count())
+        .max()
+        .unwrap_or(0);
+    let title_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let key_style = Style::default().fg(Color::Yellow);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut lines = vec![
+        Line::styled(
+            "Keys can be changed in the [keys] table of the config file, by the names on the right",
+            dim,
+        ),
+        Line::styled(
+            "e.g. restart = \"ctrl-r\", or search = \"ctrl-f\" under [keys.logs] for one place only",
+            dim,
+        ),
+    ];
+    for (context, bindings) in app.keymap.groups() {
+        lines.push(Line::raw(""));
+        lines.push(Line::from(vec![
+            Span::styled(context.title(), title_style),
+            Span::styled(format!("  [keys.{}]", context.name()), dim),
+        ]));
+        if bindings.is_empty() {
+            lines.push(Line::styled("  No keys of its own", dim));
+        }
+        for binding in bindings {
+            let label = binding.label();
+            let key = if label.is_empty() {
+                Span::styled(format!("  {:<1$}  ", UNBOUND, width), dim)
+            } else {
+                Span::styled(format!("  {:<1$}  ", label, width), key_style)
+            };
+            lines.push(Line::from(vec![
+                key,
+                Span::raw(format!("{:<1$}  ", binding.description, description_width)),
+                Span::styled(binding.action.name(), dim),
+            ]));
+        }
+    }
+
+    let area = frame.area();
+    // Borders take a row at the top and bottom
+    let height = usize::from(area.height.saturating_sub(2));
+    let max_scroll = lines.len().saturating_sub(height);
+    help.max.set(max_scroll);
+    let scroll = help.offset.min(max_scroll);
+
+    let title = if max_scroll > 0 {
+        format!(
+            " Key Bindings - rows {}-{} of {} ",
+            scroll + 1,
+            scroll + height,
+            lines.len()
+        )
+    } else {
+        " Key Bindings ".to_string()
+    };
+    let inner = render_frame(frame, area, title, Color::Cyan);
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), inner);
+}
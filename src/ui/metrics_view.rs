@@ -0,0 +1,199 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Sparkline},
+    Frame,
+};
+
+use super::dashboard::format_bytes;
+use crate::app::App;
+use crate::metrics::HistoryWindow;
+
+/// Render the full-screen metrics history view
+pub fn render_metrics_history(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(format!(
+            " Metrics History ({}) - w: change window ",
+            app.history_window.label()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    render_cpu_chart(frame, app, top[0]);
+    render_memory_chart(frame, app, top[1]);
+    render_connections_sparkline(frame, app, bottom[0]);
+    render_rates_chart(frame, app, bottom[1]);
+}
+
+fn render_cpu_chart(frame: &mut Frame, app: &App, area: Rect) {
+    let window = app.history_window;
+    let points = app
+        .metrics_history
+        .series(window, |m| m.cpu_usage_percent.clamp(0.0, 100.0));
+
+    let datasets = vec![line_dataset("CPU %", Color::Cyan, &points)];
+    let chart = Chart::new(datasets)
+        .block(chart_block(" CPU "))
+        .x_axis(time_axis(window))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, 100.0])
+                .labels(["0%", "50%", "100%"])
+                .style(Style::default().fg(Color::DarkGray)),
+        );
+    frame.render_widget(chart, area);
+}
+
+fn render_memory_chart(frame: &mut Frame, app: &App, area: Rect) {
+    let window = app.history_window;
+    let points = app
+        .metrics_history
+        .series(window, |m| m.memory_bytes as f64);
+    let limit = app
+        .metrics_history
+        .window(window)
+        .map(|s| s.metrics.memory_limit_bytes)
+        .max()
+        .unwrap_or_default();
+    let max = points
+        .iter()
+        .map(|(_, y)| *y)
+        .fold(limit as f64, f64::max)
+        .max(1.0);
+
+    let datasets = vec![line_dataset("Memory", Color::Magenta, &points)];
+    let chart = Chart::new(datasets)
+        .block(chart_block(" Memory "))
+        .x_axis(time_axis(window))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, max])
+                .labels([
+                    "0B".to_string(),
+                    format_bytes((max / 2.0) as u64),
+                    format_bytes(max as u64),
+                ])
+                .style(Style::default().fg(Color::DarkGray)),
+        );
+    frame.render_widget(chart, area);
+}
+
+fn render_connections_sparkline(frame: &mut Frame, app: &App, area: Rect) {
+    let values: Vec<u64> = app
+        .metrics_history
+        .window(app.history_window)
+        .map(|s| s.metrics.connections_active)
+        .collect();
+    let current = values.last().copied().unwrap_or_default();
+    let peak = values.iter().copied().max().unwrap_or_default();
+
+    let block = chart_block(" Active Connections ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(inner);
+
+    let summary = Paragraph::new(Line::from(vec![
+        Span::raw("Now: "),
+        Span::styled(
+            current.to_string(),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!("  Peak: {}", peak)),
+    ]));
+    frame.render_widget(summary, chunks[0]);
+
+    let data = downsample(&values, chunks[1].width as usize);
+    let sparkline = Sparkline::default()
+        .data(data)
+        .max(peak.max(1))
+        .style(Style::default().fg(Color::Green));
+    frame.render_widget(sparkline, chunks[1]);
+}
+
+fn render_rates_chart(frame: &mut Frame, app: &App, area: Rect) {
+    let window = app.history_window;
+    let (requests, errors) = app.metrics_history.rate_series(window);
+    let max = requests
+        .iter()
+        .chain(errors.iter())
+        .map(|(_, y)| *y)
+        .fold(1.0, f64::max);
+
+    let datasets = vec![
+        line_dataset("req/s", Color::Blue, &requests),
+        line_dataset("err/s", Color::Red, &errors),
+    ];
+    let chart = Chart::new(datasets)
+        .block(chart_block(" Request / Error Rate "))
+        .x_axis(time_axis(window))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, max])
+                .labels([
+                    "0".to_string(),
+                    format!("{:.1}", max / 2.0),
+                    format!("{:.1}", max),
+                ])
+                .style(Style::default().fg(Color::DarkGray)),
+        );
+    frame.render_widget(chart, area);
+}
+
+fn chart_block(title: &str) -> Block<'_> {
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::White))
+}
+
+fn line_dataset<'a>(name: &'a str, color: Color, points: &'a [(f64, f64)]) -> Dataset<'a> {
+    Dataset::default()
+        .name(name)
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(points)
+}
+
+/// X axis spanning the window, in seconds relative to now
+fn time_axis(window: HistoryWindow) -> Axis<'static> {
+    let span = window.duration().as_secs_f64();
+    Axis::default()
+        .bounds([-span, 0.0])
+        .labels([format!("-{}", window.label()), "now".to_string()])
+        .style(Style::default().fg(Color::DarkGray))
+}
+
+/// Reduce values to at most `width` buckets by averaging
+fn downsample(values: &[u64], width: usize) -> Vec<u64> {
+    if width == 0 || values.len() <= width {
+        return values.to_vec();
+    }
+    values
+        .chunks(values.len().div_ceil(width))
+        .map(|chunk| chunk.iter().sum::<u64>() / chunk.len() as u64)
+        .collect()
+}
@@ -1,3 +1,8 @@
+pub mod audit_view;
 pub mod dashboard;
+pub mod fleet_view;
+pub mod help_view;
+pub mod metrics_view;
+pub mod popup;
 
 pub use dashboard::render_dashboard;
@@ -0,0 +1,213 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::dashboard::{level_color, source_color};
+use crate::app::{App, Confirmation, PendingConfirm};
+use crate::keymap::{Action, Context, Keymap};
+
+/// Area of the given size centered in `area`, clamped to fit
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let [popup] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(popup);
+    popup
+}
+
+/// Clear `area` and draw a bordered popup frame in it, returning the inner
+/// area
+pub fn render_frame(frame: &mut Frame, area: Rect, title: String, color: Color) -> Rect {
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+    let inner = block.inner(area).inner(Margin::new(1, 0));
+    frame.render_widget(block, area);
+    inner
+}
+
+/// Most targets of a bulk action named in the confirmation popup
+const MAX_LISTED_TARGETS: usize = 8;
+
+/// Render the popup asking to confirm a control action, if one is open
+pub fn render_confirm(frame: &mut Frame, app: &App) {
+    let Some(confirm) = &app.confirm else {
+        return;
+    };
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = match confirm.targets.as_slice() {
+        [name] => vec![Line::from(vec![
+            Span::raw(format!("{} ", confirm.action.label())),
+            Span::styled(name.as_str(), bold),
+            Span::raw("?"),
+        ])],
+        targets => {
+            let mut lines = vec![Line::from(vec![
+                Span::raw(format!("{} ", confirm.action.label())),
+                Span::styled(format!("{} daemons", targets.len()), bold),
+                Span::raw("?"),
+            ])];
+            lines.extend(
+                targets
+                    .iter()
+                    .take(MAX_LISTED_TARGETS)
+                    .map(|name| Line::raw(format!("  {}", name))),
+            );
+            if targets.len() > MAX_LISTED_TARGETS {
+                lines.push(Line::raw(format!(
+                    "  and {} more",
+                    targets.len() - MAX_LISTED_TARGETS
+                )));
+            }
+            lines
+        }
+    };
+    lines.push(Line::raw(""));
+    lines.extend(prompt_lines(confirm, &app.keymap));
+
+    let area = centered_rect(frame.area(), 52, lines.len() as u16 + 2);
+    let inner = render_frame(
+        frame,
+        area,
+        format!(" Confirm {} ", confirm.action.label()),
+        Color::Red,
+    );
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn prompt_lines<'a>(confirm: &'a PendingConfirm, keymap: &Keymap) -> Vec<Line<'a>> {
+    match confirm.kind {
+        Confirmation::TypeName => {
+            let input_style = if confirm.is_satisfied() {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::Yellow)
+            };
+            vec![
+                Line::raw(if confirm.targets.len() == 1 {
+                    "Type the daemon name to confirm:"
+                } else {
+                    "Type the number of daemons to confirm:"
+                }),
+                Line::from(vec![
+                    Span::raw("> "),
+                    Span::styled(confirm.input.as_str(), input_style),
+                    Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                ]),
+                answer_keys(keymap, Context::Input),
+            ]
+        }
+        Confirmation::Prompt | Confirmation::None => vec![answer_keys(keymap, Context::Confirm)],
+    }
+}
+
+/// Keys answering the popup, as bound in `context`
+fn answer_keys(keymap: &Keymap, context: Context) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (action, text, color) in [
+        (Action::Confirm, " Confirm", Color::Green),
+        (Action::Cancel, " Cancel", Color::Red),
+    ] {
+        let Some(keys) = keymap.hint(context, &[action]) else {
+            continue;
+        };
+        if !spans.is_empty() {
+            spans.push(Span::raw(" | "));
+        }
+        spans.push(Span::styled(keys, Style::default().fg(color)));
+        spans.push(Span::raw(text));
+    }
+    Line::from(spans)
+}
+
+/// Render the popup showing the selected log entry in full and its
+/// structured fields, if one is open
+pub fn render_log_detail(frame: &mut Frame, app: &App) {
+    let Some(detail) = &app.log_detail else {
+        return;
+    };
+    let entry = &detail.entry;
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::DarkGray));
+    let timestamp = entry.timestamp.map_or_else(
+        || "unknown".to_string(),
+        |t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+    );
+    let mut lines = vec![
+        Line::from(vec![label("Time:   "), Span::raw(timestamp)]),
+        Line::from(vec![
+            label("Level:  "),
+            Span::styled(
+                entry.level.label(),
+                Style::default().fg(level_color(entry.level)),
+            ),
+        ]),
+    ];
+    lines.push(Line::from(vec![
+        label("Origin: "),
+        Span::raw(entry.origin.label()),
+    ]));
+    if !entry.source.is_empty() {
+        lines.push(Line::from(vec![
+            label("Source: "),
+            Span::styled(
+                entry.source.as_str(),
+                Style::default().fg(source_color(&entry.source)),
+            ),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.extend(entry.message.lines().map(Line::raw));
+
+    let fields = entry.fields();
+    if !fields.is_empty() {
+        lines.push(Line::raw(""));
+        let width = fields
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0);
+        let key_style = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        lines.extend(fields.into_iter().map(|(key, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<1$}  ", key, width), key_style),
+                Span::raw(value),
+            ])
+        }));
+    }
+
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(100);
+    let text = Paragraph::new(lines).wrap(Wrap { trim: false });
+    // Borders and margins take two columns on each side
+    let rows = text.line_count(width.saturating_sub(4));
+    let height = (rows as u16).min(area.height.saturating_sub(4));
+    let max_scroll = rows.saturating_sub(usize::from(height));
+    detail.scroll.max.set(max_scroll);
+    let scroll = detail.scroll.offset.min(max_scroll);
+
+    let title = if max_scroll > 0 {
+        format!(
+            " Log Entry - rows {}-{} of {} ",
+            scroll + 1,
+            scroll + usize::from(height),
+            rows
+        )
+    } else {
+        " Log Entry ".to_string()
+    };
+    let popup = centered_rect(area, width, height + 2);
+    let inner = render_frame(frame, popup, title, Color::Yellow);
+    frame.render_widget(text.scroll((scroll as u16, 0)), inner);
+}
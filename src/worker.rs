@@ -0,0 +1,215 @@
+use tokio::{sync::mpsc, task::JoinHandle, time::Instant};
+
+use crate::app::ControlAction;
+use crate::audit::Auditor;
+use crate::events::{CallError, Event};
+use daemon_controller::grpc::{daemon::LogLevel, ConnectError, DaemonClient};
+
+/// Minimum level of daemon logs to stream unless configured otherwise
+const DEFAULT_LOG_MIN_LEVEL: LogLevel = LogLevel::Debug;
+
+/// Number of historical daemon log lines to request on connect unless
+/// configured otherwise
+const DEFAULT_LOG_TAIL_LINES: u32 = 100;
+
+/// Requests sent from the UI to the client worker
+#[derive(Debug, Clone, Copy)]
+pub enum Command {
+    /// Connect to the daemon
+    Connect,
+    /// Disconnect, cancelling any pending reconnection
+    Disconnect,
+    /// Fetch status and metrics
+    Refresh,
+    /// Send a control command
+    Control(ControlAction),
+}
+
+/// Handle to the background task that owns the gRPC client.
+///
+/// All daemon calls happen on the worker so the render loop never blocks.
+/// Commands are processed in order and results are posted back as events.
+/// The worker is aborted when the handle is dropped.
+pub struct ClientWorker {
+    commands: mpsc::UnboundedSender<Command>,
+    handle: JoinHandle<()>,
+}
+
+impl ClientWorker {
+    /// Spawn the worker task
+    pub fn spawn(
+        client: DaemonClient,
+        log_min_level: Option<LogLevel>,
+        log_tail_lines: Option<u32>,
+        auditor: Auditor,
+        events: mpsc::UnboundedSender<Event>,
+    ) -> Self {
+        let (commands, rx) = mpsc::unbounded_channel();
+        let worker = Worker {
+            client,
+            auditor,
+            log_min_level: log_min_level.unwrap_or(DEFAULT_LOG_MIN_LEVEL),
+            log_tail_lines: log_tail_lines.unwrap_or(DEFAULT_LOG_TAIL_LINES),
+            events,
+        };
+        let handle = tokio::spawn(worker.run(rx));
+        Self { commands, handle }
+    }
+
+    /// Queue a command for the worker
+    pub fn send(&self, command: Command) {
+        // The worker only exits once this handle is dropped
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for ClientWorker {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// State owned by the worker task
+struct Worker {
+    client: DaemonClient,
+    auditor: Auditor,
+    log_min_level: LogLevel,
+    log_tail_lines: u32,
+    events: mpsc::UnboundedSender<Event>,
+}
+
+impl Worker {
+    /// Process commands until the handle is dropped
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<Command>) {
+        loop {
+            let command = match self.client.reconnect_state() {
+                Some(reconnect) => {
+                    tokio::select! {
+                        command = rx.recv() => command,
+                        _ = tokio::time::sleep_until(Instant::from_std(reconnect.next_retry)) => {
+                            self.reconnect().await;
+                            continue;
+                        }
+                    }
+                }
+                None => rx.recv().await,
+            };
+
+            let Some(command) = command else {
+                break;
+            };
+            match command {
+                Command::Connect => self.connect().await,
+                Command::Disconnect => self.disconnect(),
+                Command::Refresh => self.refresh().await,
+                Command::Control(action) => self.control(action).await,
+            }
+        }
+    }
+
+    fn emit(&self, event: Event) {
+        let _ = self.events.send(event);
+    }
+
+    async fn connect(&mut self) {
+        if self.client.is_connected() {
+            self.emit(Event::Connected { reconnected: false });
+            return;
+        }
+
+        match self.client.connect().await {
+            Ok(()) => self.on_connected(false).await,
+            Err(e) => self.emit(Event::ConnectFailed {
+                summary: e
+                    .downcast_ref::<ConnectError>()
+                    .map_or("Connection failed", ConnectError::summary),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    async fn reconnect(&mut self) {
+        match self.client.try_reconnect().await {
+            Some(Ok(())) => self.on_connected(true).await,
+            Some(Err(e)) => self.report_lost(e.to_string()),
+            None => {}
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.client.disconnect();
+        self.emit(Event::Disconnected);
+    }
+
+    /// Start log streaming and fetch initial data after connecting
+    async fn on_connected(&mut self, reconnected: bool) {
+        self.emit(Event::Connected { reconnected });
+        if let Err(e) =
+            self.client
+                .subscribe_logs(self.log_min_level, self.log_tail_lines, self.events.clone())
+        {
+            self.emit(Event::LogStreamDropped(format!(
+                "Failed to stream logs: {}",
+                e
+            )));
+        }
+        self.refresh().await;
+    }
+
+    async fn refresh(&mut self) {
+        let status = self.client.get_status().await.map_err(CallError::from);
+        let failed = status.as_ref().err().map(CallError::to_string);
+        self.emit(Event::StatusUpdated(status));
+        if let Some(error) = failed {
+            if self.check_lost(error) {
+                return;
+            }
+        }
+
+        let metrics = self.client.get_metrics().await.map_err(CallError::from);
+        let failed = metrics.as_ref().err().map(CallError::to_string);
+        self.emit(Event::MetricsUpdated(metrics));
+        if let Some(error) = failed {
+            self.check_lost(error);
+        }
+    }
+
+    async fn control(&mut self, action: ControlAction) {
+        let started = Instant::now();
+        let result = self
+            .client
+            .control(action.to_command())
+            .await
+            .map_err(CallError::from);
+        let failed = result.as_ref().err().map(CallError::to_string);
+        let outcome = result.as_ref().map_err(CallError::to_string);
+        if let Err(e) = self.auditor.record(action, outcome, started.elapsed()) {
+            self.emit(Event::AuditFailed(e));
+        }
+        self.emit(Event::ControlFinished(action, result));
+        if let Some(error) = failed {
+            self.check_lost(error);
+        }
+    }
+
+    /// Report a dropped connection after a failed call.
+    ///
+    /// Returns true if the client lost its connection and is now reconnecting.
+    fn check_lost(&self, error: String) -> bool {
+        if self.client.is_connected() || self.client.reconnect_state().is_none() {
+            return false;
+        }
+        self.report_lost(error);
+        true
+    }
+
+    fn report_lost(&self, error: String) {
+        if let Some(reconnect) = self.client.reconnect_state() {
+            self.emit(Event::Reconnecting {
+                attempt: reconnect.attempt,
+                next_retry: reconnect.next_retry,
+                error,
+            });
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Client and mock daemon talking over a Unix domain socket.
+
+#![cfg(unix)]
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use daemon_controller::{
+    grpc::{
+        daemon::{ControlCommand, DaemonState, LogLevel},
+        socket_path, ConnectError, DaemonClient, LogStreamEvent,
+    },
+    mock::{self, ListenAddr, MockConfig, MockDaemon, MockHandle},
+};
+use tempfile::TempDir;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Mock daemon served on a socket in its own temporary directory
+struct Server {
+    dir: TempDir,
+    handle: MockHandle,
+    task: JoinHandle<()>,
+}
+
+impl Server {
+    fn start(downtime: Duration) -> Self {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let daemon = MockDaemon::new(MockConfig {
+            log_interval: Duration::from_millis(20),
+            ..MockConfig::default()
+        });
+        let handle = daemon.handle();
+        let listen = ListenAddr::Unix(dir.path().join("daemon.sock"));
+        let task = tokio::spawn(async move {
+            mock::serve(listen, daemon, downtime, None)
+                .await
+                .expect("mock daemon failed");
+        });
+        Self { dir, handle, task }
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.dir.path().join("daemon.sock")
+    }
+
+    fn address(&self) -> String {
+        format!("unix://{}", self.socket().display())
+    }
+
+    /// Connect a client, retrying while the server binds its socket
+    async fn connect(&self) -> DaemonClient {
+        let mut client = DaemonClient::new(self.address());
+        for _ in 0..100 {
+            if client.connect().await.is_ok() {
+                return client;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("could not connect to {}", self.address());
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[test]
+fn parses_unix_addresses() {
+    assert_eq!(
+        socket_path("unix:///run/daemon.sock"),
+        Some(Path::new("/run/daemon.sock"))
+    );
+    assert_eq!(
+        socket_path("unix:daemon.sock"),
+        Some(Path::new("daemon.sock"))
+    );
+    assert_eq!(socket_path("http://127.0.0.1:50051"), None);
+}
+
+#[tokio::test]
+async fn unary_calls_over_socket() {
+    let server = Server::start(Duration::ZERO);
+    let mut client = server.connect().await;
+
+    let status = client.get_status().await.expect("get status");
+    assert_eq!(status.state(), DaemonState::Running);
+    client.get_metrics().await.expect("get metrics");
+
+    let response = client.control(ControlCommand::Stop).await.expect("control");
+    assert!(response.success, "{}", response.message);
+}
+
+#[tokio::test]
+async fn streams_logs_over_socket() {
+    let server = Server::start(Duration::ZERO);
+    let mut client = server.connect().await;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    client
+        .subscribe_logs(LogLevel::Debug, 0, tx)
+        .expect("subscribe");
+    let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("no log entry received");
+    assert!(
+        matches!(event, Some(LogStreamEvent::Entry(_))),
+        "{:?}",
+        event
+    );
+}
+
+#[tokio::test]
+async fn missing_socket_is_unreachable() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let address = format!("unix://{}", dir.path().join("missing.sock").display());
+    let mut client = DaemonClient::new(address);
+
+    let error = client.connect().await.expect_err("connect should fail");
+    let error = error.downcast_ref::<ConnectError>().expect("connect error");
+    assert!(matches!(error, ConnectError::Unreachable(_)), "{}", error);
+}
+
+#[tokio::test]
+async fn reconnects_after_server_restart() {
+    let server = Server::start(Duration::from_millis(300));
+    let mut client = server.connect().await;
+    client.get_status().await.expect("get status");
+
+    // The restarted server has to replace the socket file left behind
+    server.handle.disconnect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(server.socket().exists());
+
+    // A call while the server is down drops the connection and schedules
+    // a reconnection
+    client
+        .get_status()
+        .await
+        .expect_err("get status while the server is down");
+    assert!(!client.is_connected());
+    let reconnect = client.reconnect_state().expect("reconnection scheduled");
+    assert_eq!(reconnect.attempt, 1);
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    loop {
+        match client.try_reconnect().await {
+            Some(Ok(())) => break,
+            Some(Err(_)) | None => {
+                assert!(
+                    tokio::time::Instant::now() < deadline,
+                    "did not reconnect: {:?}",
+                    client.reconnect_state()
+                );
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }
+    }
+    assert!(client.is_connected());
+    assert!(client.reconnect_state().is_none());
+    client.get_status().await.expect("get status after restart");
+}